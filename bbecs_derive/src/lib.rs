@@ -0,0 +1,156 @@
+//! Derive macros for bbecs. These are re-exported by bbecs when its `derive` feature is
+//! enabled, so they should be used from there rather than from this crate.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, LitStr, Result};
+
+/// Implement `Component` so the type can be registered and inserted without repeating its
+/// name. The name defaults to the name of the type in lowercase words, so `LocalTransform` is
+/// stored as `"local transform"`. It can be changed with `#[component(name = "...")]`.
+#[proc_macro_derive(Component, attributes(component))]
+pub fn derive_component(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_named(&input, "component", quote!(::bbecs::components::Component))
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Implement `NamedResource` so the type can be added to the world without repeating its
+/// name. The name is chosen in the same way as for components, and can be changed with
+/// `#[resource(name = "...")]`.
+#[proc_macro_derive(Resource, attributes(resource))]
+pub fn derive_resource(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_named(
+        &input,
+        "resource",
+        quote!(::bbecs::resources::NamedResource),
+    )
+    .unwrap_or_else(Error::into_compile_error)
+    .into()
+}
+
+/// Implement `Bundle` for a struct, storing each field as a component. The name of each
+/// component defaults to the name of its field with the underscores replaced by spaces, and
+/// can be changed with `#[bundle(name = "...")]` on the field.
+#[proc_macro_derive(Bundle, attributes(bundle))]
+pub fn derive_bundle(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_bundle(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_named(
+    input: &DeriveInput,
+    attribute: &str,
+    trait_path: TokenStream2,
+) -> Result<TokenStream2> {
+    let ident = &input.ident;
+    let name = match name_attribute(&input.attrs, attribute)? {
+        Some(name) => name,
+        None => words_from_camel_case(&ident.to_string()),
+    };
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #trait_path for #ident #type_generics #where_clause {
+            const NAME: &'static str = #name;
+        }
+    })
+}
+
+fn expand_bundle(input: &DeriveInput) -> Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "bundles must be structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "bundles must be structs with named fields",
+            ))
+        }
+    };
+
+    let mut components = vec![];
+    for field in fields {
+        let field_ident = field.ident.as_ref().expect("named fields have identifiers");
+        let name = match name_attribute(&field.attrs, "bundle")? {
+            Some(name) => name,
+            None => field_ident
+                .to_string()
+                .trim_start_matches("r#")
+                .replace('_', " "),
+        };
+        components.push(quote! {
+            (
+                ::std::string::String::from(#name),
+                ::bbecs::components::ComponentData::new(self.#field_ident),
+            )
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::bbecs::world::bundle::Bundle for #ident #type_generics #where_clause {
+            fn into_components(
+                self,
+            ) -> ::std::vec::Vec<(::std::string::String, ::bbecs::components::ComponentData)> {
+                ::std::vec![#(#components),*]
+            }
+        }
+    })
+}
+
+/// Find `#[attribute(name = "...")]` among the attributes
+fn name_attribute(attributes: &[Attribute], attribute: &str) -> Result<Option<String>> {
+    let mut name = None;
+    for attr in attributes
+        .iter()
+        .filter(|attr| attr.path().is_ident(attribute))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                let value: LitStr = meta.value()?.parse()?;
+                name = Some(value.value());
+                Ok(())
+            } else {
+                Err(meta.error(format!("unknown {} attribute", attribute)))
+            }
+        })?;
+    }
+    Ok(name)
+}
+
+/// Split a type name into lowercase words, so `LocalTransform` becomes `local transform`
+fn words_from_camel_case(name: &str) -> String {
+    let mut words = String::new();
+    let mut previous: Option<char> = None;
+    let mut characters = name.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        if character.is_uppercase() {
+            let after_lowercase =
+                previous.is_some_and(|previous| previous.is_lowercase() || previous.is_numeric());
+            let before_lowercase = characters.peek().is_some_and(|next| next.is_lowercase());
+            let in_acronym = previous.is_some_and(char::is_uppercase);
+            if !words.is_empty() && (after_lowercase || (in_acronym && before_lowercase)) {
+                words.push(' ');
+            }
+        }
+        words.extend(character.to_lowercase());
+        previous = Some(character);
+    }
+
+    words
+}
@@ -4,17 +4,18 @@
 ///
 /// ```
 /// use bbecs::query;
-/// use bbecs::world::{World, ENTITY_ID, WorldMethods, DataWrapper};
+/// use bbecs::world::{World, Entity, ENTITY_ID, DataWrapper};
 /// use bbecs::components::CastComponents;
 ///
 /// // creating the world and adding an entity for us to query later
 /// let mut world = World::new();
 ///     world.register("size").unwrap();
-///     world
-///         .spawn_entity()
-///         .unwrap()
-///         .with_component("size", 15.0_f32)
-///         .unwrap();
+/// let entity = world
+///     .spawn_entity()
+///     .unwrap()
+///     .with_component("size", 15.0_f32)
+///     .unwrap()
+///     .entity();
 ///
 /// // Now we are querying for the size and an entity id which is automatically added to all entities
 /// let query;
@@ -22,18 +23,62 @@
 ///
 /// for (index, size) in sizes.iter().enumerate() {
 ///     let size: &DataWrapper<f32> = size.cast().unwrap();
-///     let entity_id: &DataWrapper<u32> = entity_ids[index].cast().unwrap();
+///     let entity_id: &DataWrapper<Entity> = entity_ids[index].cast().unwrap();
 ///
 ///     assert_eq!(*size.borrow(), 15.0);
-///     assert_eq!(*entity_id.borrow(), 0);
+///     assert_eq!(*entity_id.borrow(), entity);
 /// }
 /// ```
+///
+/// Optional components can be listed after the required ones, and are returned at the end of
+/// the tuple with `None` for entities that do not have them. Entities can also be filtered by
+/// the components they have or do not have, without fetching those components.
+///
+/// ```
+/// use bbecs::query;
+/// use bbecs::world::World;
+///
+/// let mut world = World::new();
+/// world.register("size").unwrap();
+/// world.register("health").unwrap();
+/// world.register("frozen").unwrap();
+/// world.spawn_entity().unwrap().with_component("size", 1.0_f32).unwrap();
+/// world
+///     .spawn_entity()
+///     .unwrap()
+///     .with_component("size", 2.0_f32)
+///     .unwrap()
+///     .with_component("health", 10_u32)
+///     .unwrap();
+/// world
+///     .spawn_entity()
+///     .unwrap()
+///     .with_component("size", 3.0_f32)
+///     .unwrap()
+///     .with_component("frozen", true)
+///     .unwrap();
+///
+/// let query;
+/// let (sizes, healths) = query!(world, query, "size"; optional "health"; without "frozen");
+///
+/// assert_eq!(sizes.len(), 2);
+/// assert_eq!(healths.iter().filter(|health| health.is_some()).count(), 1);
+/// ```
 macro_rules! query {
-    ($world:expr, $query:expr, $($name:expr),*) => {{
-        $query = $world.query(vec![$($name,)*]).unwrap();
+    ($world:expr, $query:expr, $($name:expr),*
+        $(; optional $($optional:expr),+)?
+        $(; with $($with:expr),+)?
+        $(; without $($without:expr),+)?
+    ) => {{
+        let filter = $crate::world::query::QueryFilter::new()
+            $($(.optional($optional))+)?
+            $($(.with($with))+)?
+            $($(.without($without))+)?;
+        $query = $world.query_filtered(vec![$($name,)*], &filter).unwrap();
 
         (
             $($query.get($name).unwrap(),)*
+            $($($query.get_optional($optional).unwrap(),)+)?
         )
     }};
 }
@@ -1,77 +1,177 @@
 pub mod helpers;
 
-use std::cell::RefCell;
-use std::rc::Rc;
-
-use eyre::{bail, Result};
-use ggez::audio::SoundData;
-use ggez::event::KeyCode;
-use ggez::graphics::{Color, Mesh, Text};
-
-use crate::data_types::point::Point;
-
-macro_rules! impl_component_data_cast {
-    ($new_type:ty, $arm:ident) => {
-        impl CastComponents<$new_type> for ComponentData {
-            fn cast(&self) -> Result<&Rc<RefCell<$new_type>>> {
-                if let ComponentData::$arm(value) = self {
-                    Ok(value)
-                } else {
-                    bail!(
-                        "Error casting component from {:?} to {}",
-                        self,
-                        stringify!($new_type)
-                    )
-                }
-            }
+use std::any::TypeId;
+use std::fmt::{self, Debug};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::errors::{BbEcsError, Result};
+use crate::world::data_wrapper::{self, AnyBox, DataWrapper, Storable};
+
+#[cfg(feature = "derive")]
+pub use bbecs_derive::Component;
+
+pub trait CastComponents<T> {
+    fn cast(&self) -> Result<&DataWrapper<T>>;
+}
+
+/// A type that is always stored under the same component name, so that it can be registered
+/// and inserted without repeating the name. With the `derive` feature this can be derived,
+/// naming the component after the type.
+///
+/// ```
+/// use bbecs::components::Component;
+/// use bbecs::world::World;
+///
+/// struct Health(u32);
+///
+/// impl Component for Health {
+///     const NAME: &'static str = "health";
+/// }
+///
+/// let mut world = World::new();
+/// world.register_component::<Health>().unwrap();
+/// let entity = world.spawn_entity().unwrap().with(Health(10)).unwrap().entity();
+/// assert!(world.has_component(entity, "health").unwrap());
+/// ```
+pub trait Component: Storable {
+    const NAME: &'static str;
+}
+
+/// These components are used to store data into the world. Each component holds a single
+/// piece of data of any `Storable` type, which can be retrieved again by casting to the type
+/// that was inserted. Generally consumers of this library will not need to create Components
+/// directly. However the methods attached to components will be used to extract the data.
+/// Components stored in a world know the name they are stored under, so that errors casting
+/// them can say which component it was.
+pub struct ComponentData {
+    data: AnyBox,
+    name: Option<String>,
+    type_id: TypeId,
+    type_name: &'static str,
+    ticks: ComponentTicks,
+}
+
+impl ComponentData {
+    pub fn new<T: Storable>(data: T) -> Self {
+        Self {
+            data: data_wrapper::into_any(data_wrapper::wrap(data)),
+            name: None,
+            type_id: TypeId::of::<T>(),
+            type_name: std::any::type_name::<T>(),
+            ticks: ComponentTicks::default(),
+        }
+    }
+
+    /// Check if the data stored in this component is of type T
+    /// ```
+    /// use bbecs::components::ComponentData;
+    /// let component = ComponentData::new(15.0_f32);
+    /// assert!(component.is::<f32>());
+    /// assert!(!component.is::<u32>());
+    /// ```
+    pub fn is<T: 'static>(&self) -> bool {
+        self.type_id == TypeId::of::<T>()
+    }
+
+    /// Move the data out of the component. This fails if the data is not of type T, or if
+    /// the wrapper around the data has been cloned and is still in use.
+    pub fn into_inner<T: Storable>(self) -> Result<T> {
+        let type_name = self.type_name;
+        match self.data.downcast::<DataWrapper<T>>() {
+            Ok(wrapper) => match data_wrapper::unwrap(*wrapper) {
+                Some(data) => Ok(data),
+                None => Err(BbEcsError::ComponentInUse(type_name)),
+            },
+            Err(_) => Err(BbEcsError::CastingComponents(
+                self.name,
+                type_name,
+                std::any::type_name::<T>(),
+            )),
+        }
+    }
+
+    /// The name the component is stored under, once it has been stored in a world
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub(crate) fn set_name(&mut self, name: &str) {
+        if self.name.as_deref() != Some(name) {
+            self.name = Some(name.to_owned());
         }
-    };
+    }
+
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    pub fn ticks(&self) -> &ComponentTicks {
+        &self.ticks
+    }
 }
 
-pub trait CastComponents<T> {
-    fn cast(&self) -> Result<&Rc<RefCell<T>>>;
+impl<T: Storable> CastComponents<T> for ComponentData {
+    fn cast(&self) -> Result<&DataWrapper<T>> {
+        if let Some(value) = self.data.downcast_ref() {
+            Ok(value)
+        } else {
+            Err(BbEcsError::CastingComponents(
+                self.name.clone(),
+                self.type_name,
+                std::any::type_name::<T>(),
+            ))
+        }
+    }
 }
 
-/// These components are used to store data into the world. Each of the components contains
-/// a vector of the appropriate data. Generally consumers of this library will not need to
-/// call Components directly. However the methods attached to components will be used to
-/// extract the data.
-#[derive(Debug, Clone)]
-pub enum ComponentData {
-    Point(Rc<RefCell<Point>>),
-    F32(Rc<RefCell<f32>>),
-    Color(Rc<RefCell<Color>>),
-    Mesh(Rc<RefCell<Mesh>>),
-    U32(Rc<RefCell<u32>>),
-    Usize(Rc<RefCell<usize>>),
-    Bool(Rc<RefCell<bool>>),
-    GgezKeyCode(Rc<RefCell<KeyCode>>),
-    Marker(Rc<RefCell<String>>),
-    GgezText(Rc<RefCell<Text>>),
-    GgezSound(Rc<RefCell<ggez::audio::SoundData>>),
+impl Debug for ComponentData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ComponentData")
+            .field("name", &self.name)
+            .field("type_name", &self.type_name)
+            .field("ticks", &self.ticks)
+            .finish()
+    }
 }
 
-impl_component_data_cast!(Point, Point);
-impl_component_data_cast!(f32, F32);
-impl_component_data_cast!(Color, Color);
-impl_component_data_cast!(Mesh, Mesh);
-impl_component_data_cast!(u32, U32);
-impl_component_data_cast!(usize, Usize);
-impl_component_data_cast!(bool, Bool);
-impl_component_data_cast!(KeyCode, GgezKeyCode);
-impl_component_data_cast!(String, Marker);
-impl_component_data_cast!(Text, GgezText);
-impl_component_data_cast!(SoundData, GgezSound);
-
-pub enum Component {
-    Point,
-    F32,
-    Color,
-    Mesh,
-    U32,
-    Usize,
-    Bool,
-    GgezKeyCode,
-    Marker,
-    GgezText,
+/// The world change ticks at which a component was added to its entity and last changed.
+/// Components are marked as changed when they are replaced, or when they are mutably
+/// dereferenced through a typed query.
+#[derive(Debug, Default)]
+pub struct ComponentTicks {
+    added: AtomicU32,
+    changed: AtomicU32,
+}
+
+impl ComponentTicks {
+    pub fn added(&self) -> u32 {
+        self.added.load(Ordering::Relaxed)
+    }
+
+    pub fn changed(&self) -> u32 {
+        self.changed.load(Ordering::Relaxed)
+    }
+
+    /// Check if the component was added after the given tick
+    pub fn is_added_since(&self, tick: u32) -> bool {
+        self.added() > tick
+    }
+
+    /// Check if the component was added or changed after the given tick
+    pub fn is_changed_since(&self, tick: u32) -> bool {
+        self.changed() > tick
+    }
+
+    pub(crate) fn set_added(&self, tick: u32) {
+        self.added.store(tick, Ordering::Relaxed);
+        self.set_changed(tick);
+    }
+
+    pub(crate) fn set_changed(&self, tick: u32) {
+        self.changed.store(tick, Ordering::Relaxed);
+    }
 }
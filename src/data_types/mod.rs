@@ -1 +1,2 @@
 pub mod point;
+pub mod transform;
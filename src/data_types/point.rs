@@ -4,6 +4,7 @@ use std::ops::{AddAssign, Div, Sub};
 /// Point that stores a f32 x and y with methods for manipulating the point. Uses Vector math
 /// for the methods
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     pub x: f32,
     pub y: f32,
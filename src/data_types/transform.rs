@@ -0,0 +1,83 @@
+use super::point::Point;
+
+/// Position, rotation and scale of an entity. Rotation is in radians, and scale multiplies
+/// the x and y axis separately before rotating.
+///
+/// An entity's local transform is relative to its parent, and its global transform is worked
+/// out from the local transforms of every entity above it in the hierarchy.
+/// ```
+/// use bbecs::data_types::point::Point;
+/// use bbecs::data_types::transform::Transform;
+///
+/// let ship = Transform::new(Point::new(10.0, 0.0)).with_scale(Point::new(2.0, 2.0));
+/// let turret = Transform::new(Point::new(1.0, 0.0));
+/// assert_eq!(ship.mul_transform(&turret).position, Point::new(12.0, 0.0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transform {
+    pub position: Point,
+    pub rotation: f32,
+    pub scale: Point,
+}
+
+impl Transform {
+    pub fn new(position: Point) -> Self {
+        Self {
+            position,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Point) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Move a point from the space of this transform out into the space it is relative to
+    /// ```
+    /// use bbecs::data_types::point::Point;
+    /// use bbecs::data_types::transform::Transform;
+    ///
+    /// let transform = Transform::new(Point::new(5.0, 5.0)).with_rotation(std::f32::consts::PI);
+    /// let point = transform.transform_point(Point::new(1.0, 0.0));
+    /// assert!((point.x - 4.0).abs() < 0.0001);
+    /// assert!((point.y - 5.0).abs() < 0.0001);
+    /// ```
+    pub fn transform_point(&self, point: Point) -> Point {
+        let x = point.x * self.scale.x;
+        let y = point.y * self.scale.y;
+        let (sin, cos) = self.rotation.sin_cos();
+
+        Point::new(
+            x * cos - y * sin + self.position.x,
+            x * sin + y * cos + self.position.y,
+        )
+    }
+
+    /// Combine this transform with a transform that is relative to it, giving the transform
+    /// relative to whatever this one is relative to. This is how a child's global transform
+    /// is made from its parent's global transform and its own local transform.
+    pub fn mul_transform(&self, child: &Transform) -> Transform {
+        Transform {
+            position: self.transform_point(child.position),
+            rotation: self.rotation + child.rotation,
+            scale: Point::new(self.scale.x * child.scale.x, self.scale.y * child.scale.y),
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            position: Point::new(0.0, 0.0),
+            rotation: 0.0,
+            scale: Point::new(1.0, 1.0),
+        }
+    }
+}
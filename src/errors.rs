@@ -1,11 +1,21 @@
+use std::fmt::{self, Display};
+
 use thiserror::Error;
 
+use crate::world::commands::CommandError;
+use crate::world::Entity;
+
+/// The result returned throughout the public API of bbecs
+pub type Result<T, E = BbEcsError> = std::result::Result<T, E>;
+
 #[derive(Debug, Error)]
 pub enum BbEcsError {
-    #[error("attempted to cast component from `{0}`")]
-    CastingComponents(&'static str),
-    #[error("attempted to cast resource from to `{0}`")]
-    CastingResource(&'static str),
+    /// The name of the component if it is known, the type it stores and the type it was cast to
+    #[error("component{} stores `{1}` and cannot be cast to `{2}`", named(.0))]
+    CastingComponents(Option<String>, &'static str, &'static str),
+    /// The name of the resource if it is known, the type it stores and the type it was cast to
+    #[error("resource{} stores `{1}` and cannot be cast to `{2}`", named(.0))]
+    CastingResource(Option<String>, &'static str, &'static str),
     #[error("you need to register before inserting components")]
     NeedToRegister,
     #[error("component with name `{0}` not found")]
@@ -14,12 +24,103 @@ pub enum BbEcsError {
     ResourceNotFound(String),
     #[error("already registered component with name `{0}`")]
     ComponentAlreadyRegistered(String),
-    #[error("tried to insert into the bitmap before registering")]
-    BitMapInsertBeforeRegister,
-    #[error("BitMap component `{0}` not found")]
-    BitMapComponentNotFound(String),
+    #[error("component `{0}` stores `{1}` and cannot also store `{2}`")]
+    ComponentTypeMismatch(String, &'static str, &'static str),
+    #[error("typed query expected {0} component names but was given {1}")]
+    QueryNameCountMismatch(usize, usize),
+    #[error("typed query names the component `{0}` more than once")]
+    DuplicateQueryName(String),
+    #[error("entity `{0}` is not alive")]
+    EntityNotAlive(Entity),
+    #[error("entity `{0}` does not have the component `{1}`")]
+    EntityMissingComponent(Entity, String),
+    #[error("component of type `{0}` is still in use and cannot be moved out of the world")]
+    ComponentInUse(&'static str),
+    #[error("component `{0}` is managed by the world and cannot be changed directly")]
+    BuiltInComponent(String),
+    #[error("a bundle cannot have more than one component named `{0}`")]
+    DuplicateComponent(String),
+    #[error("entity `{1}` cannot be the parent of `{0}` as it is `{0}` or one of its descendants")]
+    HierarchyCycle(Entity, Entity),
+    #[error("no type has been registered to load saved data of type `{0}`")]
+    UnknownSavedType(String),
+    #[error("saved data refers to entity `{0}` which is not in the save")]
+    EntityNotSaved(Entity),
+    #[error("prefab `{0}` not found")]
+    PrefabNotFound(String),
+    #[error("prefab `{0}` field `{1}` {2}")]
+    InvalidPrefab(String, String, String),
+    #[error("snapshot is version {0} but only version {1} can be restored")]
+    SnapshotVersion(u32, u32),
+    #[error("snapshot data is corrupted: {0}")]
+    SnapshotCorrupted(&'static str),
+    #[error("stage `{0}` not found in the schedule")]
+    StageNotFound(String),
+    #[error("the schedule already has a stage named `{0}`")]
+    StageAlreadyExists(String),
+    #[error("`{0}` cannot be {1} as it is already borrowed in a way that conflicts")]
+    BorrowConflict(String, BorrowAccess),
+    #[error("{} queued commands failed", .0.len())]
+    CommandsFailed(Vec<CommandError>),
+    #[cfg(feature = "serialize")]
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "serialize")]
+    #[error(transparent)]
+    Ron(#[from] ron::Error),
+    #[cfg(feature = "serialize")]
+    #[error(transparent)]
+    RonSyntax(#[from] ron::error::SpannedError),
+    #[cfg(feature = "serialize")]
+    #[error(transparent)]
+    Toml(#[from] toml::de::Error),
+    /// Any other error, so that systems and commands can use `?` on `eyre` results
+    #[cfg(feature = "eyre")]
+    #[error(transparent)]
+    Other(#[from] eyre::Report),
     #[error(
         "You tried to access a vector with an index that is greater than the length of the vector"
     )]
     OutOfRangeInVector,
 }
+
+impl BbEcsError {
+    /// Fill in the name of the component or resource on a cast error that was made without
+    /// knowing it. Other errors are returned unchanged.
+    pub fn with_name(self, name: &str) -> Self {
+        match self {
+            BbEcsError::CastingComponents(None, stored, expected) => {
+                BbEcsError::CastingComponents(Some(name.to_owned()), stored, expected)
+            }
+            BbEcsError::CastingResource(None, stored, expected) => {
+                BbEcsError::CastingResource(Some(name.to_owned()), stored, expected)
+            }
+            error => error,
+        }
+    }
+}
+
+fn named(name: &Option<String>) -> String {
+    match name {
+        Some(name) => format!(" `{}`", name),
+        None => String::new(),
+    }
+}
+
+/// The kind of access to a component or resource that could not be borrowed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorrowAccess {
+    /// Borrowing immutably, which conflicts with a live mutable borrow
+    Read,
+    /// Borrowing mutably, which conflicts with any other live borrow
+    Write,
+}
+
+impl Display for BorrowAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BorrowAccess::Read => write!(f, "read"),
+            BorrowAccess::Write => write!(f, "written"),
+        }
+    }
+}
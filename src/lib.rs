@@ -2,4 +2,5 @@ pub mod components;
 pub mod data_types;
 pub mod errors;
 pub mod resources;
+pub mod systems;
 pub mod world;
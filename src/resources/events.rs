@@ -0,0 +1,163 @@
+use std::marker::PhantomData;
+
+use crate::errors::Result;
+use crate::world::{Storable, World};
+
+use super::resource::ResourceCast;
+
+#[derive(Debug)]
+struct EventInstance<T> {
+    id: usize,
+    event: T,
+}
+
+/// A channel of events of one type that systems can use to talk to each other. Events are
+/// stored in two buffers which are swapped on every `World::update`, so each event is kept
+/// for two updates. Any reader that reads at least once per update will see every event
+/// exactly once, whether it runs before or after the system that sent the event.
+///
+/// Events are added to the world as a resource with `World::add_events`, which also makes
+/// sure that the buffers are swapped during updates.
+///
+/// ```
+/// use bbecs::resources::events::{EventReader, Events};
+///
+/// let mut events = Events::new();
+/// let mut reader = EventReader::new();
+///
+/// events.writer().send("ball hit paddle");
+/// assert_eq!(reader.read(&events).collect::<Vec<_>>(), vec![&"ball hit paddle"]);
+/// assert_eq!(reader.read(&events).count(), 0);
+///
+/// events.update();
+/// events.update();
+/// assert!(events.is_empty());
+/// ```
+#[derive(Debug)]
+pub struct Events<T> {
+    previous: Vec<EventInstance<T>>,
+    current: Vec<EventInstance<T>>,
+    event_count: usize,
+}
+
+impl<T> Events<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn send(&mut self, event: T) {
+        self.current.push(EventInstance {
+            id: self.event_count,
+            event,
+        });
+        self.event_count += 1;
+    }
+
+    pub fn writer(&mut self) -> EventWriter<'_, T> {
+        EventWriter { events: self }
+    }
+
+    /// Swap the buffers, dropping the events that were sent before the last update. This is
+    /// called by `World::update` for events that were added with `World::add_events`.
+    pub fn update(&mut self) {
+        self.previous = std::mem::take(&mut self.current);
+    }
+
+    /// Drop every event straight away
+    pub fn clear(&mut self) {
+        self.previous.clear();
+        self.current.clear();
+    }
+
+    /// The number of events that are still being kept
+    pub fn len(&self) -> usize {
+        self.previous.len() + self.current.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.previous.is_empty() && self.current.is_empty()
+    }
+
+    fn iter_from(&self, first_id: usize) -> impl Iterator<Item = &T> {
+        self.previous
+            .iter()
+            .chain(self.current.iter())
+            .filter(move |instance| instance.id >= first_id)
+            .map(|instance| &instance.event)
+    }
+}
+
+impl<T> Default for Events<T> {
+    fn default() -> Self {
+        Self {
+            previous: vec![],
+            current: vec![],
+            event_count: 0,
+        }
+    }
+}
+
+/// Sends events into an `Events` channel
+pub struct EventWriter<'a, T> {
+    events: &'a mut Events<T>,
+}
+
+impl<'a, T> EventWriter<'a, T> {
+    pub fn send(&mut self, event: T) {
+        self.events.send(event);
+    }
+
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = T>) {
+        for event in events {
+            self.events.send(event);
+        }
+    }
+}
+
+/// Remembers which events have already been read from an `Events` channel. Each system that
+/// reads events should keep its own reader between runs.
+#[derive(Debug)]
+pub struct EventReader<T> {
+    next_id: usize,
+    event_type: PhantomData<fn() -> T>,
+}
+
+impl<T> EventReader<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Iterate over the events that this reader has not seen yet
+    pub fn read<'a>(&mut self, events: &'a Events<T>) -> impl Iterator<Item = &'a T> {
+        let first_id = self.next_id;
+        self.next_id = events.event_count;
+        events.iter_from(first_id)
+    }
+
+    /// The number of events that this reader has not seen yet
+    pub fn len(&self, events: &Events<T>) -> usize {
+        events.iter_from(self.next_id).count()
+    }
+
+    pub fn is_empty(&self, events: &Events<T>) -> bool {
+        self.len(events) == 0
+    }
+}
+
+impl<T> Default for EventReader<T> {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            event_type: PhantomData,
+        }
+    }
+}
+
+/// Swap the buffers of the events stored as the named resource, failing instead of panicking
+/// if they are already borrowed
+pub(crate) fn update_events<T: Storable>(world: &World, name: &str) -> Result<()> {
+    let mut wrapper = world.try_borrow_resource_mut(name)?;
+    let events: &mut Events<T> = wrapper.cast_mut().map_err(|error| error.with_name(name))?;
+    events.update();
+    Ok(())
+}
@@ -50,3 +50,71 @@ macro_rules! get_resource_mut {
         $resource = wrapper.cast_mut().unwrap();
     };
 }
+
+/// Get a reference to a resource in the world like `get_resource!`, but return the error with
+/// `?` instead of panicking. This includes when the resource is missing, is a different type,
+/// or is already mutably borrowed somewhere else.
+///
+/// ```
+/// use bbecs::world::World;
+/// use bbecs::{get_resource_mut, try_get_resource};
+/// use crate::bbecs::world::WorldMethods;
+/// use crate::bbecs::resources::resource::ResourceCast;
+///
+/// fn read_size(world: &World) -> bbecs::errors::Result<f32> {
+///     let size: &f32;
+///     try_get_resource!(size, world, "size");
+///     Ok(*size)
+/// }
+///
+/// let mut world = World::new();
+/// world.add_resource("size".to_string(), 15.0_f32);
+/// assert_eq!(read_size(&world).unwrap(), 15.0_f32);
+///
+/// let size: &mut f32;
+/// get_resource_mut!(size, world, "size");
+/// assert!(read_size(&world).is_err());
+/// ```
+#[macro_export]
+macro_rules! try_get_resource {
+    ($resource:ident, $world:expr, $name:expr) => {
+        let name: String = $name.into();
+        let wrapper = $world.try_borrow_resource(name.as_str())?;
+        $resource = wrapper.cast().map_err(|error| error.with_name(&name))?;
+    };
+}
+
+/// Get a mutable reference to a resource like `get_resource_mut!`, but return the error with
+/// `?` instead of panicking. This includes when the resource is missing, is a different type,
+/// or is already borrowed somewhere else.
+///
+/// ```
+/// use bbecs::world::World;
+/// use bbecs::{get_resource, try_get_resource_mut};
+/// use crate::bbecs::world::WorldMethods;
+/// use crate::bbecs::resources::resource::ResourceCast;
+///
+/// fn grow(world: &World) -> bbecs::errors::Result<()> {
+///     let size: &mut f32;
+///     try_get_resource_mut!(size, world, "size");
+///     *size += 1.0;
+///     Ok(())
+/// }
+///
+/// let mut world = World::new();
+/// world.add_resource("size".to_string(), 15.0_f32);
+/// grow(&world).unwrap();
+///
+/// let size: &f32;
+/// get_resource!(size, world, "size");
+/// assert_eq!(*size, 16.0_f32);
+/// assert!(grow(&world).is_err());
+/// ```
+#[macro_export]
+macro_rules! try_get_resource_mut {
+    ($resource:ident, $world:expr, $name:expr) => {
+        let name: String = $name.into();
+        let mut wrapper = $world.try_borrow_resource_mut(name.as_str())?;
+        $resource = wrapper.cast_mut().map_err(|error| error.with_name(&name))?;
+    };
+}
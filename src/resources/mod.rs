@@ -1,3 +1,16 @@
+pub mod events;
 pub mod helpers;
 pub mod resource;
 pub mod resources_data;
+
+#[cfg(feature = "derive")]
+pub use bbecs_derive::Resource;
+
+use crate::world::Storable;
+
+/// A type that is always stored under the same resource name, so that it can be added to the
+/// world without repeating the name. With the `derive` feature this can be derived with
+/// `#[derive(Resource)]`, naming the resource after the type.
+pub trait NamedResource: Storable {
+    const NAME: &'static str;
+}
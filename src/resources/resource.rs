@@ -1,68 +1,82 @@
-use eyre::{bail, Result};
-use ggez::audio::SoundData;
-use ggez::event::KeyCode;
-use ggez::graphics::{Color, Mesh, Text};
+use std::any::TypeId;
+use std::fmt::{self, Debug};
 
-use crate::data_types::point::Point;
-
-macro_rules! impl_resource_cast {
-    ($new_type:ty, $arm:ident) => {
-        impl ResourceCast<$new_type> for Resource {
-            fn cast(&self) -> Result<&$new_type> {
-                if let Resource::$arm(value) = self {
-                    Ok(value)
-                } else {
-                    bail!(
-                        "Error casting {} to resource of type {}",
-                        format!("{:?}", self),
-                        stringify!($new_type)
-                    )
-                }
-            }
-
-            fn cast_mut(&mut self) -> Result<&mut $new_type> {
-                if let Resource::$arm(value) = self {
-                    Ok(value)
-                } else {
-                    bail!(
-                        "Error casting {} to resource of type {}",
-                        format!("{:?}", self),
-                        stringify!($new_type)
-                    )
-                }
-            }
-        }
-    };
-}
+use crate::errors::{BbEcsError, Result};
+use crate::world::data_wrapper::{self, AnyBox, Storable};
 
 pub trait ResourceCast<T> {
     fn cast(&self) -> Result<&T>;
     fn cast_mut(&mut self) -> Result<&mut T>;
 }
 
-#[derive(Debug, Clone)]
-pub enum Resource {
-    Color(Color),
-    Mesh(Mesh),
-    Point(Point),
-    U32(u32),
-    F32(f32),
-    Usize(usize),
-    Bool(bool),
-    GgezKeyCode(KeyCode),
-    Marker(String),
-    GgezText(Text),
-    GgezSound(ggez::audio::SoundData),
+/// A single resource stored in the world. Resources can be any `Storable` type and are
+/// retrieved by casting back to the type that was added.
+pub struct Resource {
+    data: AnyBox,
+    type_id: TypeId,
+    type_name: &'static str,
+}
+
+impl Resource {
+    pub fn new<T: Storable>(data: T) -> Self {
+        Self {
+            data: data_wrapper::into_any(data),
+            type_id: TypeId::of::<T>(),
+            type_name: std::any::type_name::<T>(),
+        }
+    }
+
+    /// Check if the data stored in this resource is of type T
+    /// ```
+    /// use bbecs::resources::resource::Resource;
+    /// let resource = Resource::new(15.0_f32);
+    /// assert!(resource.is::<f32>());
+    /// assert!(!resource.is::<u32>());
+    /// ```
+    pub fn is<T: 'static>(&self) -> bool {
+        self.type_id == TypeId::of::<T>()
+    }
+
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+impl<T: Storable> ResourceCast<T> for Resource {
+    fn cast(&self) -> Result<&T> {
+        if let Some(value) = self.data.downcast_ref() {
+            Ok(value)
+        } else {
+            Err(BbEcsError::CastingResource(
+                None,
+                self.type_name,
+                std::any::type_name::<T>(),
+            ))
+        }
+    }
+
+    fn cast_mut(&mut self) -> Result<&mut T> {
+        let type_name = self.type_name;
+        if let Some(value) = self.data.downcast_mut() {
+            Ok(value)
+        } else {
+            Err(BbEcsError::CastingResource(
+                None,
+                type_name,
+                std::any::type_name::<T>(),
+            ))
+        }
+    }
 }
 
-impl_resource_cast!(u32, U32);
-impl_resource_cast!(Point, Point);
-impl_resource_cast!(Color, Color);
-impl_resource_cast!(Mesh, Mesh);
-impl_resource_cast!(f32, F32);
-impl_resource_cast!(usize, Usize);
-impl_resource_cast!(bool, Bool);
-impl_resource_cast!(KeyCode, GgezKeyCode);
-impl_resource_cast!(String, Marker);
-impl_resource_cast!(Text, GgezText);
-impl_resource_cast!(SoundData, GgezSound);
+impl Debug for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Resource")
+            .field("type_name", &self.type_name)
+            .finish()
+    }
+}
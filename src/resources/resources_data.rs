@@ -1,15 +1,13 @@
-use std::cell::RefCell;
 use std::collections::HashMap;
-use std::rc::Rc;
 
-use eyre::Result;
-
-use crate::errors::BbEcsError;
+use crate::errors::{BbEcsError, Result};
+use crate::world::data_wrapper::{self, DataWrapper};
 
 use super::resource::Resource;
 
+#[derive(Default)]
 pub struct ResourcesData {
-    resources: HashMap<String, Rc<RefCell<Resource>>>,
+    resources: HashMap<String, DataWrapper<Resource>>,
 }
 
 impl ResourcesData {
@@ -19,22 +17,18 @@ impl ResourcesData {
 
     /// Inserts a resource into storage, overwriting any resource that had the same name
     pub fn insert(&mut self, name: String, resource: Resource) {
-        self.resources.insert(name, Rc::new(RefCell::new(resource)));
+        self.resources.insert(name, data_wrapper::wrap(resource));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &DataWrapper<Resource>)> {
+        self.resources.iter()
     }
 
-    pub fn get(&self, name: &str) -> Result<&Rc<RefCell<Resource>>> {
+    pub fn get(&self, name: &str) -> Result<&DataWrapper<Resource>> {
         if let Some(resource) = self.resources.get(name) {
             Ok(resource)
         } else {
-            Err(BbEcsError::ResourceNotFound(name.to_owned()).into())
-        }
-    }
-}
-
-impl Default for ResourcesData {
-    fn default() -> Self {
-        Self {
-            resources: HashMap::new(),
+            Err(BbEcsError::ResourceNotFound(name.to_owned()))
         }
     }
 }
@@ -0,0 +1,81 @@
+use std::collections::BTreeSet;
+
+use crate::world::TO_BE_DELETED;
+
+/// The components and resources that a system reads and writes. Systems whose access does
+/// not conflict can run at the same time.
+///
+/// ```
+/// use bbecs::systems::access::Access;
+///
+/// let movement = Access::new()
+///     .read_component("velocity")
+///     .write_component("location");
+/// let rendering = Access::new()
+///     .read_component("location")
+///     .read_resource("camera");
+/// let input = Access::new().write_resource("keyboard");
+///
+/// assert!(!movement.is_compatible(&rendering));
+/// assert!(movement.is_compatible(&input));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Access {
+    component_reads: BTreeSet<String>,
+    component_writes: BTreeSet<String>,
+    resource_reads: BTreeSet<String>,
+    resource_writes: BTreeSet<String>,
+}
+
+impl Access {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn read_component(mut self, name: &str) -> Self {
+        self.component_reads.insert(name.to_owned());
+        self
+    }
+
+    pub fn write_component(mut self, name: &str) -> Self {
+        self.component_writes.insert(name.to_owned());
+        self
+    }
+
+    pub fn read_resource(mut self, name: &str) -> Self {
+        self.resource_reads.insert(name.to_owned());
+        self
+    }
+
+    pub fn write_resource(mut self, name: &str) -> Self {
+        self.resource_writes.insert(name.to_owned());
+        self
+    }
+
+    /// Needed by systems that call `World::delete_by_id`. Every query checks whether
+    /// entities have been flagged to be deleted, so these systems can not run at the same
+    /// time as any other system.
+    pub fn delete_entities(self) -> Self {
+        self.write_component(TO_BE_DELETED)
+    }
+
+    /// Check if two systems can safely run at the same time
+    pub fn is_compatible(&self, other: &Access) -> bool {
+        if self.component_writes.contains(TO_BE_DELETED)
+            || other.component_writes.contains(TO_BE_DELETED)
+        {
+            return false;
+        }
+
+        !(writes_conflict(&self.component_writes, &other.component_reads)
+            || writes_conflict(&self.component_writes, &other.component_writes)
+            || writes_conflict(&other.component_writes, &self.component_reads)
+            || writes_conflict(&self.resource_writes, &other.resource_reads)
+            || writes_conflict(&self.resource_writes, &other.resource_writes)
+            || writes_conflict(&other.resource_writes, &self.resource_reads))
+    }
+}
+
+fn writes_conflict(writes: &BTreeSet<String>, other: &BTreeSet<String>) -> bool {
+    !writes.is_disjoint(other)
+}
@@ -0,0 +1,50 @@
+pub mod access;
+pub mod schedule;
+
+use crate::errors::Result;
+use crate::world::commands::Commands;
+use crate::world::World;
+
+use self::access::Access;
+
+/// Game logic that runs against the world. Any function or closure that takes the world
+/// mutably and returns a `Result` is a system, so most systems do not need to implement
+/// this trait themselves.
+pub trait System {
+    fn run(&mut self, world: &mut World) -> Result<()>;
+}
+
+impl<F> System for F
+where
+    F: FnMut(&mut World) -> Result<()>,
+{
+    fn run(&mut self, world: &mut World) -> Result<()> {
+        self(world)
+    }
+}
+
+/// A system that only needs shared access to the world, so that it can run at the same time
+/// as other parallel systems. Each system declares the components and resources it uses, and
+/// the schedule only runs systems together when none of them write to something the others
+/// use. Structural changes such as spawning are recorded into the commands, which are applied
+/// once every system running alongside it has finished.
+///
+/// Systems only run on multiple threads when the `parallel` feature is enabled, otherwise they
+/// are run one after another.
+pub trait ParallelSystem: Send {
+    fn access(&self) -> Access;
+    fn run(&mut self, world: &World, commands: &mut Commands) -> Result<()>;
+}
+
+impl<F> ParallelSystem for (Access, F)
+where
+    F: FnMut(&World, &mut Commands) -> Result<()> + Send,
+{
+    fn access(&self) -> Access {
+        self.0.clone()
+    }
+
+    fn run(&mut self, world: &World, commands: &mut Commands) -> Result<()> {
+        (self.1)(world, commands)
+    }
+}
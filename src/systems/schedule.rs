@@ -0,0 +1,299 @@
+use crate::errors::{BbEcsError, Result};
+use crate::world::commands::Commands;
+use crate::world::World;
+
+use super::{ParallelSystem, System};
+
+pub const INPUT: &str = "input";
+pub const UPDATE: &str = "update";
+pub const POST_UPDATE: &str = "post update";
+pub const CLEANUP: &str = "cleanup";
+
+enum ScheduledSystem {
+    Exclusive(Box<dyn System>),
+    Parallel(Box<dyn ParallelSystem>),
+}
+
+struct Stage {
+    name: String,
+    systems: Vec<ScheduledSystem>,
+}
+
+impl Stage {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            systems: vec![],
+        }
+    }
+}
+
+/// Runs systems in order, grouped into named stages. By default there are input, update,
+/// post update and cleanup stages, and `World::update` is called after the cleanup stage
+/// has run so that deleted entities are removed at the end of every run.
+///
+/// ```
+/// use bbecs::systems::schedule::{Schedule, UPDATE};
+/// use bbecs::world::World;
+///
+/// let mut world = World::new();
+/// world.register("size").unwrap();
+/// world.spawn_entity().unwrap().with_component("size", 1_u32).unwrap();
+///
+/// let mut schedule = Schedule::new();
+/// schedule
+///     .add_system(UPDATE, |world: &mut World| {
+///         for (mut size,) in world.query_typed::<(&mut u32,)>(["size"])? {
+///             *size += 1;
+///         }
+///         Ok(())
+///     })
+///     .unwrap();
+///
+/// schedule.run(&mut world).unwrap();
+/// let (size,) = world.query_typed::<(&u32,)>(["size"]).unwrap().next().unwrap();
+/// assert_eq!(*size, 2);
+/// ```
+pub struct Schedule {
+    stages: Vec<Stage>,
+    world_update_after: Option<String>,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a schedule without any stages that never calls `World::update`
+    pub fn empty() -> Self {
+        Self {
+            stages: vec![],
+            world_update_after: None,
+        }
+    }
+
+    /// Add a new stage that runs after all of the existing stages
+    pub fn add_stage(&mut self, name: &str) -> Result<&mut Self> {
+        self.check_stage_is_new(name)?;
+        self.stages.push(Stage::new(name.to_owned()));
+        Ok(self)
+    }
+
+    /// Add a new stage that runs straight after an existing stage
+    pub fn add_stage_after(&mut self, existing: &str, name: &str) -> Result<&mut Self> {
+        self.check_stage_is_new(name)?;
+        let index = self.stage_index(existing)?;
+        self.stages.insert(index + 1, Stage::new(name.to_owned()));
+        Ok(self)
+    }
+
+    /// Add a new stage that runs just before an existing stage
+    pub fn add_stage_before(&mut self, existing: &str, name: &str) -> Result<&mut Self> {
+        self.check_stage_is_new(name)?;
+        let index = self.stage_index(existing)?;
+        self.stages.insert(index, Stage::new(name.to_owned()));
+        Ok(self)
+    }
+
+    /// Add a system to the end of a stage. Systems in a stage run in the order they were added.
+    pub fn add_system<S: System + 'static>(&mut self, stage: &str, system: S) -> Result<&mut Self> {
+        let index = self.stage_index(stage)?;
+        self.stages[index]
+            .systems
+            .push(ScheduledSystem::Exclusive(Box::new(system)));
+        Ok(self)
+    }
+
+    /// Add a parallel system to the end of a stage. Parallel systems that are next to each
+    /// other in a stage are grouped into batches that have no conflicting access, and each
+    /// batch runs at the same time. A system always runs after any earlier system in the
+    /// stage that it conflicts with.
+    pub fn add_parallel_system<S: ParallelSystem + 'static>(
+        &mut self,
+        stage: &str,
+        system: S,
+    ) -> Result<&mut Self> {
+        let index = self.stage_index(stage)?;
+        self.stages[index]
+            .systems
+            .push(ScheduledSystem::Parallel(Box::new(system)));
+        Ok(self)
+    }
+
+    /// Call `World::update` once the given stage has finished running
+    pub fn run_world_update_after(&mut self, stage: &str) -> Result<&mut Self> {
+        self.stage_index(stage)?;
+        self.world_update_after = Some(stage.to_owned());
+        Ok(self)
+    }
+
+    /// Stop the schedule from calling `World::update`, so it can be called by hand instead
+    pub fn disable_world_update(&mut self) -> &mut Self {
+        self.world_update_after = None;
+        self
+    }
+
+    /// Run every system in every stage once. Stops at the first system that fails.
+    pub fn run(&mut self, world: &mut World) -> Result<()> {
+        for stage in &mut self.stages {
+            let mut index = 0;
+            while index < stage.systems.len() {
+                if let ScheduledSystem::Exclusive(system) = &mut stage.systems[index] {
+                    world.increment_change_tick();
+                    system.run(world)?;
+                    index += 1;
+                    continue;
+                }
+
+                let end = stage.systems[index..]
+                    .iter()
+                    .position(|system| matches!(system, ScheduledSystem::Exclusive(_)))
+                    .map_or(stage.systems.len(), |offset| index + offset);
+                let mut parallel_systems = stage.systems[index..end]
+                    .iter_mut()
+                    .filter_map(|system| match system {
+                        ScheduledSystem::Parallel(system) => Some(system),
+                        ScheduledSystem::Exclusive(_) => None,
+                    })
+                    .collect();
+                run_parallel_systems(&mut parallel_systems, world)?;
+                index = end;
+            }
+
+            if self.world_update_after.as_deref() == Some(stage.name.as_str()) {
+                world.update()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn stage_index(&self, name: &str) -> Result<usize> {
+        if let Some(index) = self.stages.iter().position(|stage| stage.name == name) {
+            Ok(index)
+        } else {
+            Err(BbEcsError::StageNotFound(name.to_owned()))
+        }
+    }
+
+    fn check_stage_is_new(&self, name: &str) -> Result<()> {
+        if self.stages.iter().any(|stage| stage.name == name) {
+            return Err(BbEcsError::StageAlreadyExists(name.to_owned()));
+        }
+        Ok(())
+    }
+}
+
+/// Split the systems into batches where no two systems in a batch conflict. Each system is
+/// placed in the batch after the last one holding a system it conflicts with, so conflicting
+/// systems keep the order they were added in.
+fn create_batches(systems: &[&mut Box<dyn ParallelSystem>]) -> Vec<Vec<usize>> {
+    let accesses: Vec<_> = systems.iter().map(|system| system.access()).collect();
+    let mut batches: Vec<Vec<usize>> = vec![];
+
+    for (index, access) in accesses.iter().enumerate() {
+        let batch_index = batches
+            .iter()
+            .rposition(|batch| {
+                batch
+                    .iter()
+                    .any(|other| !access.is_compatible(&accesses[*other]))
+            })
+            .map_or(0, |conflicting_batch| conflicting_batch + 1);
+
+        if batch_index == batches.len() {
+            batches.push(vec![]);
+        }
+        batches[batch_index].push(index);
+    }
+
+    batches
+}
+
+fn run_parallel_systems(
+    systems: &mut Vec<&mut Box<dyn ParallelSystem>>,
+    world: &mut World,
+) -> Result<()> {
+    for batch in create_batches(systems) {
+        let mut batch_systems: Vec<_> = systems
+            .iter_mut()
+            .enumerate()
+            .filter(|(index, _)| batch.contains(index))
+            .map(|(_, system)| system)
+            .collect();
+
+        world.increment_change_tick();
+        let results = run_batch(&mut batch_systems, world);
+        let mut command_errors = vec![];
+        let mut first_error = None;
+        for (result, commands) in results {
+            if let Err(error) = result {
+                first_error.get_or_insert(error);
+            }
+            command_errors.extend(world.apply_commands(commands));
+        }
+
+        if let Some(error) = first_error {
+            return Err(error);
+        }
+        if !command_errors.is_empty() {
+            return Err(BbEcsError::CommandsFailed(command_errors));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "parallel")]
+fn run_batch(
+    systems: &mut [&mut &mut Box<dyn ParallelSystem>],
+    world: &World,
+) -> Vec<(Result<()>, Commands)> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = systems
+            .iter_mut()
+            .map(|system| {
+                scope.spawn(move || {
+                    let mut commands = Commands::new();
+                    let result = system.run(world, &mut commands);
+                    (result, commands)
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| match handle.join() {
+                Ok(result) => result,
+                Err(panic) => std::panic::resume_unwind(panic),
+            })
+            .collect()
+    })
+}
+
+#[cfg(not(feature = "parallel"))]
+fn run_batch(
+    systems: &mut [&mut &mut Box<dyn ParallelSystem>],
+    world: &World,
+) -> Vec<(Result<()>, Commands)> {
+    systems
+        .iter_mut()
+        .map(|system| {
+            let mut commands = Commands::new();
+            let result = system.run(world, &mut commands);
+            (result, commands)
+        })
+        .collect()
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            stages: [INPUT, UPDATE, POST_UPDATE, CLEANUP]
+                .iter()
+                .map(|name| Stage::new((*name).to_owned()))
+                .collect(),
+            world_update_after: Some(CLEANUP.to_owned()),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::components::ComponentData;
+
+use super::{DeletionStrategy, Entity};
+
+/// A table holding every entity that has exactly the same set of components. Each component
+/// name has its own column, and the components for one entity are at the same row in every
+/// column.
+#[derive(Debug)]
+pub struct Archetype {
+    names: BTreeSet<String>,
+    columns: HashMap<String, Vec<ComponentData>>,
+    entities: Vec<Entity>,
+}
+
+impl Archetype {
+    pub fn new(names: BTreeSet<String>) -> Self {
+        let columns = names.iter().map(|name| (name.clone(), vec![])).collect();
+
+        Self {
+            names,
+            columns,
+            entities: vec![],
+        }
+    }
+
+    pub fn names(&self) -> &BTreeSet<String> {
+        &self.names
+    }
+
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn has_component(&self, name: &str) -> bool {
+        self.columns.contains_key(name)
+    }
+
+    pub fn has_all_components(&self, names: &[&str]) -> bool {
+        names.iter().all(|name| self.has_component(name))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = (&String, &Vec<ComponentData>)> {
+        self.columns.iter()
+    }
+
+    pub fn column(&self, name: &str) -> Option<&Vec<ComponentData>> {
+        self.columns.get(name)
+    }
+
+    pub fn column_mut(&mut self, name: &str) -> Option<&mut Vec<ComponentData>> {
+        self.columns.get_mut(name)
+    }
+
+    /// Add an entity to the end of the table, returning the row it was placed in. There must
+    /// be exactly one component for each column in the table, and each component is given the
+    /// name of its column.
+    pub fn push(
+        &mut self,
+        entity: Entity,
+        mut components: HashMap<String, ComponentData>,
+    ) -> usize {
+        for (name, column) in self.columns.iter_mut() {
+            let mut component = components
+                .remove(name)
+                .expect("archetype received an entity without all of its components");
+            component.set_name(name);
+            column.push(component);
+        }
+        self.entities.push(entity);
+        self.entities.len() - 1
+    }
+
+    /// Take an entity out of the table by swapping the last row into its place. Returns the
+    /// components of the removed entity, and the entity that was moved into the row if any.
+    pub fn swap_remove(&mut self, row: usize) -> (HashMap<String, ComponentData>, Option<Entity>) {
+        let components = self
+            .columns
+            .iter_mut()
+            .map(|(name, column)| (name.clone(), column.swap_remove(row)))
+            .collect();
+        self.entities.swap_remove(row);
+
+        (components, self.entities.get(row).copied())
+    }
+
+    /// Take every entity out of the table along with its components
+    pub fn take_all(&mut self) -> Vec<(Entity, HashMap<String, ComponentData>)> {
+        let mut rows = vec![];
+        while let Some(entity) = self.entities.last().copied() {
+            let (components, _) = self.swap_remove(self.len() - 1);
+            rows.push((entity, components));
+        }
+        rows
+    }
+
+    /// Take many entities out of the table at once. The rows must be sorted from lowest to
+    /// highest. When keeping the order every column is compacted in a single pass, otherwise
+    /// the last rows are swapped into the gaps. Returns the removed entities along with the
+    /// first row whose entity has changed.
+    pub fn remove_rows(
+        &mut self,
+        rows: &[usize],
+        strategy: DeletionStrategy,
+    ) -> (Vec<Entity>, usize) {
+        let first_changed_row = match rows.first() {
+            Some(row) => *row,
+            None => return (vec![], self.len()),
+        };
+        let removed_entities = rows.iter().map(|row| self.entities[*row]).collect();
+
+        match strategy {
+            DeletionStrategy::PreserveOrder => {
+                let mut to_remove = vec![false; self.len()];
+                for row in rows {
+                    to_remove[*row] = true;
+                }
+                for column in self.columns.values_mut() {
+                    retain_unmarked(column, &to_remove);
+                }
+                retain_unmarked(&mut self.entities, &to_remove);
+            }
+            DeletionStrategy::SwapRemove => {
+                for row in rows.iter().rev() {
+                    for column in self.columns.values_mut() {
+                        column.swap_remove(*row);
+                    }
+                    self.entities.swap_remove(*row);
+                }
+            }
+        }
+
+        (removed_entities, first_changed_row)
+    }
+}
+
+fn retain_unmarked<T>(items: &mut Vec<T>, to_remove: &[bool]) {
+    let mut index = 0;
+    items.retain(|_| {
+        let keep = !to_remove[index];
+        index += 1;
+        keep
+    });
+}
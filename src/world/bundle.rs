@@ -0,0 +1,82 @@
+#[cfg(feature = "derive")]
+pub use bbecs_derive::Bundle;
+
+use crate::components::ComponentData;
+
+use super::Storable;
+
+/// A group of named components that are spawned onto an entity together with
+/// `World::spawn_bundle`. Every component is checked before the entity is spawned, so a bundle
+/// either spawns whole or not at all.
+///
+/// Bundles are implemented for tuples of up to eight `(name, data)` pairs, and can be
+/// implemented for structs by giving each field a component name. With the `derive` feature
+/// `#[derive(Bundle)]` does this, naming each component after its field.
+///
+/// ```
+/// use bbecs::components::ComponentData;
+/// use bbecs::data_types::point::Point;
+/// use bbecs::world::bundle::Bundle;
+/// use bbecs::world::World;
+///
+/// struct Ship {
+///     location: Point,
+///     size: f32,
+/// }
+///
+/// impl Bundle for Ship {
+///     fn into_components(self) -> Vec<(String, ComponentData)> {
+///         vec![
+///             ("location".to_owned(), ComponentData::new(self.location)),
+///             ("size".to_owned(), ComponentData::new(self.size)),
+///         ]
+///     }
+/// }
+///
+/// let mut world = World::new();
+/// world.register("location").unwrap();
+/// world.register("size").unwrap();
+///
+/// let ship = Ship {
+///     location: Point::new(5.0, 5.0),
+///     size: 10.0,
+/// };
+/// let ship = world.spawn_bundle(ship).unwrap().entity();
+/// let asteroid = world
+///     .spawn_bundle((("location", Point::new(0.0, 0.0)), ("size", 25.0_f32)))
+///     .unwrap()
+///     .entity();
+/// assert!(world.has_component(asteroid, "size").unwrap());
+/// ```
+pub trait Bundle {
+    /// The name and data of every component in the bundle
+    fn into_components(self) -> Vec<(String, ComponentData)>;
+}
+
+macro_rules! impl_bundle {
+    ($($data:ident: $index:tt),+) => {
+        impl<'a, $($data: Storable),+> Bundle for ($((&'a str, $data),)+) {
+            fn into_components(self) -> Vec<(String, ComponentData)> {
+                vec![$(
+                    ((self.$index).0.to_owned(), ComponentData::new((self.$index).1)),
+                )+]
+            }
+        }
+    };
+}
+
+/// Components that were already put together by name, such as the components of a prefab
+impl Bundle for Vec<(String, ComponentData)> {
+    fn into_components(self) -> Vec<(String, ComponentData)> {
+        self
+    }
+}
+
+impl_bundle!(A: 0);
+impl_bundle!(A: 0, B: 1);
+impl_bundle!(A: 0, B: 1, C: 2);
+impl_bundle!(A: 0, B: 1, C: 2, D: 3);
+impl_bundle!(A: 0, B: 1, C: 2, D: 3, E: 4);
+impl_bundle!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+impl_bundle!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+impl_bundle!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
@@ -0,0 +1,181 @@
+use std::fmt::{self, Display};
+
+use crate::errors::{BbEcsError, Result};
+
+use super::bundle::Bundle;
+use super::{Entity, Storable, World};
+
+#[cfg(not(feature = "parallel"))]
+type WorldCommand = Box<dyn FnOnce(&mut World) -> Result<()>>;
+#[cfg(not(feature = "parallel"))]
+type EntityCommand = Box<dyn FnOnce(&mut World, Entity) -> Result<()>>;
+#[cfg(feature = "parallel")]
+type WorldCommand = Box<dyn FnOnce(&mut World) -> Result<()> + Send + Sync>;
+#[cfg(feature = "parallel")]
+type EntityCommand = Box<dyn FnOnce(&mut World, Entity) -> Result<()> + Send + Sync>;
+
+enum Command {
+    Spawn(Vec<EntityCommand>),
+    Apply(WorldCommand),
+}
+
+/// Records changes to the world so that they can be made after a query has finished
+/// borrowing it. The commands are applied in the order they were recorded, either straight
+/// away with `World::apply_commands`, or during the next `World::update` after being given
+/// to `World::queue_commands`.
+///
+/// ```
+/// use bbecs::world::commands::Commands;
+/// use bbecs::world::World;
+///
+/// let mut world = World::new();
+/// world.register("size").unwrap();
+/// world.spawn_entity().unwrap().with_component("size", 5_u32).unwrap();
+///
+/// let mut commands = Commands::new();
+/// for (size,) in world.query_typed::<(&u32,)>(["size"]).unwrap() {
+///     commands.spawn().with_component("size", *size * 2);
+/// }
+/// world.queue_commands(commands);
+/// world.update().unwrap();
+///
+/// assert_eq!(world.query_typed::<(&u32,)>(["size"]).unwrap().len(), 2);
+/// ```
+#[derive(Default)]
+pub struct Commands {
+    commands: Vec<Command>,
+}
+
+impl Commands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spawn a new entity, returning a builder to record the components it will be given
+    pub fn spawn(&mut self) -> SpawnCommands<'_> {
+        self.commands.push(Command::Spawn(vec![]));
+        if let Some(Command::Spawn(components)) = self.commands.last_mut() {
+            SpawnCommands { components }
+        } else {
+            unreachable!("a spawn command was just added")
+        }
+    }
+
+    /// Spawn a new entity with every component in the bundle
+    pub fn spawn_bundle<B: Bundle + Storable>(&mut self, bundle: B) {
+        self.add(move |world| world.spawn_bundle(bundle).map(|_| ()));
+    }
+
+    pub fn insert_component<T: Storable>(&mut self, entity: Entity, name: &str, data: T) {
+        let name = name.to_owned();
+        self.add(move |world| world.insert_component(entity, &name, data));
+    }
+
+    /// Remove a component from the entity, dropping the data that was stored in it
+    pub fn remove_component<T: Storable>(&mut self, entity: Entity, name: &str) {
+        let name = name.to_owned();
+        self.add(move |world| world.remove_component::<T>(entity, &name).map(|_| ()));
+    }
+
+    pub fn despawn(&mut self, entity: Entity) {
+        self.add(move |world| world.despawn(entity));
+    }
+
+    /// Despawn the entity along with all of its descendants
+    pub fn despawn_recursive(&mut self, entity: Entity) {
+        self.add(move |world| world.despawn_recursive(entity));
+    }
+
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) {
+        self.add(move |world| world.set_parent(child, parent));
+    }
+
+    pub fn remove_parent(&mut self, child: Entity) {
+        self.add(move |world| world.remove_parent(child).map(|_| ()));
+    }
+
+    /// Record any other change to the world
+    pub fn add<F: FnOnce(&mut World) -> Result<()> + Storable>(&mut self, command: F) {
+        self.commands.push(Command::Apply(Box::new(command)));
+    }
+
+    /// Move all of the commands from the other buffer onto the end of this one
+    pub fn append(&mut self, other: &mut Commands) {
+        self.commands.append(&mut other.commands);
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Apply every command in order. A command that fails does not stop the following
+    /// commands from being applied, and a spawn that fails does not leave a partly built
+    /// entity behind.
+    pub(crate) fn apply(self, world: &mut World) -> Vec<CommandError> {
+        let mut errors = vec![];
+
+        for (index, command) in self.commands.into_iter().enumerate() {
+            let result = match command {
+                Command::Spawn(components) => spawn(world, components),
+                Command::Apply(command) => command(world),
+            };
+
+            if let Err(error) = result {
+                errors.push(CommandError { index, error });
+            }
+        }
+
+        errors
+    }
+}
+
+fn spawn(world: &mut World, components: Vec<EntityCommand>) -> Result<()> {
+    let entity = world.spawn_entity()?.entity();
+    for component in components {
+        if let Err(error) = component(world, entity) {
+            world.despawn(entity)?;
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+/// Records the components for an entity spawned through `Commands`
+pub struct SpawnCommands<'a> {
+    components: &'a mut Vec<EntityCommand>,
+}
+
+impl<'a> SpawnCommands<'a> {
+    pub fn with_component<T: Storable>(&mut self, name: &str, data: T) -> &mut Self {
+        let name = name.to_owned();
+        self.components.push(Box::new(move |world, entity| {
+            world.insert_component(entity, &name, data)
+        }));
+        self
+    }
+
+    /// Make the spawned entity a child of the parent
+    pub fn with_parent(&mut self, parent: Entity) -> &mut Self {
+        self.components.push(Box::new(move |world, entity| {
+            world.set_parent(entity, parent)
+        }));
+        self
+    }
+}
+
+/// A command that could not be applied, along with its position in the buffer
+#[derive(Debug)]
+pub struct CommandError {
+    pub index: usize,
+    pub error: BbEcsError,
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "command {} failed: {}", self.index, self.error)
+    }
+}
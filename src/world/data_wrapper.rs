@@ -0,0 +1,159 @@
+//! The wrapper that every component and resource is stored in. By default this is an
+//! `Rc<RefCell<T>>`. With the `parallel` feature enabled it is an `Arc<SyncCell<T>>` instead,
+//! so that the world can be shared between the threads running systems.
+
+use crate::errors::{BbEcsError, BorrowAccess, Result};
+
+#[cfg(not(feature = "parallel"))]
+mod storage {
+    use std::any::Any;
+    use std::cell::{Ref, RefCell, RefMut};
+    use std::rc::Rc;
+
+    pub type DataWrapper<T> = Rc<RefCell<T>>;
+    pub type DataRef<'a, T> = Ref<'a, T>;
+    pub type DataRefMut<'a, T> = RefMut<'a, T>;
+    pub type AnyBox = Box<dyn Any>;
+
+    /// Everything stored in the world needs to be `'static` so that it can be cast back to
+    /// its own type.
+    pub trait Storable: Any {}
+
+    impl<T: Any> Storable for T {}
+
+    pub fn wrap<T>(data: T) -> DataWrapper<T> {
+        Rc::new(RefCell::new(data))
+    }
+
+    pub fn is_shared<T>(wrapper: &DataWrapper<T>) -> bool {
+        Rc::strong_count(wrapper) > 1
+    }
+
+    pub fn unwrap<T>(wrapper: DataWrapper<T>) -> Option<T> {
+        Rc::try_unwrap(wrapper).ok().map(RefCell::into_inner)
+    }
+
+    pub fn try_read<T>(wrapper: &DataWrapper<T>) -> Option<DataRef<'_, T>> {
+        wrapper.try_borrow().ok()
+    }
+
+    pub fn try_write<T>(wrapper: &DataWrapper<T>) -> Option<DataRefMut<'_, T>> {
+        wrapper.try_borrow_mut().ok()
+    }
+}
+
+#[cfg(feature = "parallel")]
+mod storage {
+    use std::any::Any;
+    use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+
+    pub type DataWrapper<T> = Arc<SyncCell<T>>;
+    pub type DataRef<'a, T> = RwLockReadGuard<'a, T>;
+    pub type DataRefMut<'a, T> = RwLockWriteGuard<'a, T>;
+    pub type AnyBox = Box<dyn Any + Send + Sync>;
+
+    /// Everything stored in the world needs to be `'static` so that it can be cast back to
+    /// its own type, and safe to share between threads.
+    pub trait Storable: Any + Send + Sync {}
+
+    impl<T: Any + Send + Sync> Storable for T {}
+
+    /// A thread safe version of `RefCell`. Borrowing panics instead of blocking when the data
+    /// is already borrowed in a conflicting way, in the same way that `RefCell` does.
+    #[derive(Debug, Default)]
+    pub struct SyncCell<T> {
+        lock: RwLock<T>,
+    }
+
+    impl<T> SyncCell<T> {
+        pub fn new(data: T) -> Self {
+            Self {
+                lock: RwLock::new(data),
+            }
+        }
+
+        pub fn borrow(&self) -> DataRef<'_, T> {
+            match self.lock.try_read() {
+                Ok(data) => data,
+                Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+                Err(TryLockError::WouldBlock) => panic!("already mutably borrowed"),
+            }
+        }
+
+        pub fn borrow_mut(&self) -> DataRefMut<'_, T> {
+            match self.lock.try_write() {
+                Ok(data) => data,
+                Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+                Err(TryLockError::WouldBlock) => panic!("already borrowed"),
+            }
+        }
+
+        /// Borrow the data, or `None` if it is already mutably borrowed
+        pub fn try_borrow(&self) -> Option<DataRef<'_, T>> {
+            match self.lock.try_read() {
+                Ok(data) => Some(data),
+                Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+                Err(TryLockError::WouldBlock) => None,
+            }
+        }
+
+        /// Mutably borrow the data, or `None` if it is already borrowed
+        pub fn try_borrow_mut(&self) -> Option<DataRefMut<'_, T>> {
+            match self.lock.try_write() {
+                Ok(data) => Some(data),
+                Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+                Err(TryLockError::WouldBlock) => None,
+            }
+        }
+
+        pub fn into_inner(self) -> T {
+            match self.lock.into_inner() {
+                Ok(data) => data,
+                Err(poisoned) => poisoned.into_inner(),
+            }
+        }
+    }
+
+    pub fn wrap<T>(data: T) -> DataWrapper<T> {
+        Arc::new(SyncCell::new(data))
+    }
+
+    pub fn is_shared<T>(wrapper: &DataWrapper<T>) -> bool {
+        Arc::strong_count(wrapper) > 1
+    }
+
+    pub fn unwrap<T>(wrapper: DataWrapper<T>) -> Option<T> {
+        Arc::try_unwrap(wrapper).ok().map(SyncCell::into_inner)
+    }
+
+    pub fn try_read<T>(wrapper: &DataWrapper<T>) -> Option<DataRef<'_, T>> {
+        wrapper.try_borrow()
+    }
+
+    pub fn try_write<T>(wrapper: &DataWrapper<T>) -> Option<DataRefMut<'_, T>> {
+        wrapper.try_borrow_mut()
+    }
+}
+
+pub use storage::*;
+
+/// Borrow the data stored under the name, failing instead of panicking when it is already
+/// mutably borrowed
+pub(crate) fn try_borrow<'a, T>(wrapper: &'a DataWrapper<T>, name: &str) -> Result<DataRef<'a, T>> {
+    try_read(wrapper).ok_or_else(|| BbEcsError::BorrowConflict(name.to_owned(), BorrowAccess::Read))
+}
+
+/// Mutably borrow the data stored under the name, failing instead of panicking when it is
+/// already borrowed
+pub(crate) fn try_borrow_mut<'a, T>(
+    wrapper: &'a DataWrapper<T>,
+    name: &str,
+) -> Result<DataRefMut<'a, T>> {
+    try_write(wrapper)
+        .ok_or_else(|| BbEcsError::BorrowConflict(name.to_owned(), BorrowAccess::Write))
+}
+
+/// Box the data up so that it can be stored without knowing its type
+pub(crate) fn into_any<T: Storable>(data: T) -> AnyBox {
+    Box::new(data)
+}
@@ -0,0 +1,130 @@
+use std::fmt::{self, Display};
+
+/// Handle to an entity in the world. The index is reused once an entity is deleted, so the
+/// generation is used to tell an old handle apart from the entity that took its place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    /// A handle that is never alive. Loading a save puts this in place of any entity that
+    /// was stored in a component or resource but is not in the save itself.
+    pub const DANGLING: Entity = Entity {
+        index: u32::MAX,
+        generation: u32::MAX,
+    };
+
+    pub(crate) fn new(index: u32, generation: u32) -> Self {
+        Self { index, generation }
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+impl Display for Entity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}v{}", self.index, self.generation)
+    }
+}
+
+/// Where the components of an entity are stored, as the index of its archetype and the row
+/// inside of that archetype.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntityLocation {
+    pub archetype: usize,
+    pub row: usize,
+}
+
+/// Keeps track of which entity handles are alive, and where in the world's storage each of
+/// them lives.
+#[derive(Debug, Default)]
+pub struct Entities {
+    generations: Vec<u32>,
+    locations: Vec<Option<EntityLocation>>,
+    free_indexes: Vec<u32>,
+}
+
+impl Entities {
+    /// Create a new entity handle, reusing the index of a deleted entity if there is one
+    pub fn allocate(&mut self, location: EntityLocation) -> Entity {
+        if let Some(index) = self.free_indexes.pop() {
+            self.locations[index as usize] = Some(location);
+            Entity {
+                index,
+                generation: self.generations[index as usize],
+            }
+        } else {
+            self.generations.push(0);
+            self.locations.push(Some(location));
+            Entity {
+                index: self.generations.len() as u32 - 1,
+                generation: 0,
+            }
+        }
+    }
+
+    /// Release the entity so that its index can be reused. Any handles to it will no longer
+    /// be alive.
+    pub fn free(&mut self, entity: Entity) {
+        if self.is_alive(entity) {
+            let index = entity.index as usize;
+            self.generations[index] += 1;
+            self.locations[index] = None;
+            self.free_indexes.push(entity.index);
+        }
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let index = entity.index as usize;
+        index < self.generations.len()
+            && self.generations[index] == entity.generation
+            && self.locations[index].is_some()
+    }
+
+    pub fn location(&self, entity: Entity) -> Option<EntityLocation> {
+        if self.is_alive(entity) {
+            self.locations[entity.index as usize]
+        } else {
+            None
+        }
+    }
+
+    pub fn set_location(&mut self, entity: Entity, location: EntityLocation) {
+        if self.is_alive(entity) {
+            self.locations[entity.index as usize] = Some(location);
+        }
+    }
+
+    pub fn generations(&self) -> &[u32] {
+        &self.generations
+    }
+
+    pub fn free_indexes(&self) -> &[u32] {
+        &self.free_indexes
+    }
+
+    /// Go back to an earlier state where the entities had these generations and free indexes.
+    /// No entities are alive until they are revived.
+    pub fn restore(&mut self, generations: Vec<u32>, free_indexes: Vec<u32>) {
+        self.locations = vec![None; generations.len()];
+        self.generations = generations;
+        self.free_indexes = free_indexes;
+    }
+
+    /// Make the entity alive again at the location, as long as its generation is current
+    pub fn revive(&mut self, entity: Entity, location: EntityLocation) {
+        let index = entity.index as usize;
+        if index < self.generations.len() && self.generations[index] == entity.generation {
+            self.locations[index] = Some(location);
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use crate::components::Component;
+use crate::errors::Result;
+
+use super::{Entity, Storable, World};
+
+/// Returned when spawning an entity so that components can be chained onto it.
+pub struct EntityBuilder<'a> {
+    world: &'a mut World,
+    entity: Entity,
+}
+
+impl<'a> EntityBuilder<'a> {
+    pub(crate) fn new(world: &'a mut World, entity: Entity) -> Self {
+        Self { world, entity }
+    }
+
+    /// Add a component to the entity being built. Any `Storable` type can be used as a
+    /// component as long as the name has been registered first.
+    pub fn with_component<T: Storable>(&mut self, name: &str, data: T) -> Result<&mut Self> {
+        self.world.insert_component(self.entity, name, data)?;
+        Ok(self)
+    }
+
+    /// Add a component to the entity being built under the name for its type
+    pub fn with<T: Component>(&mut self, data: T) -> Result<&mut Self> {
+        self.with_component(T::NAME, data)
+    }
+
+    /// Make the entity being built a child of the parent
+    pub fn with_parent(&mut self, parent: Entity) -> Result<&mut Self> {
+        self.world.set_parent(self.entity, parent)?;
+        Ok(self)
+    }
+
+    /// The handle of the entity being built
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+}
@@ -1,213 +1,445 @@
-use std::cell::RefCell;
-use std::collections::{BTreeMap, HashMap};
-use std::rc::Rc;
+use std::any::TypeId;
+use std::collections::{BTreeSet, HashMap};
 
-use eyre::Result;
-use ggez::event::KeyCode;
-use ggez::graphics::{Color, Mesh, Text};
+use crate::components::{CastComponents, ComponentData};
+use crate::errors::{BbEcsError, Result};
 
-use crate::errors::BbEcsError;
-use crate::{components::ComponentData, data_types::point::Point};
+use super::archetype::Archetype;
+use super::data_wrapper::{self, DataWrapper, Storable};
+use super::entity::{Entities, EntityLocation};
+use super::query::{QueryFilter, QueryResults};
+use super::{DeletionStrategy, Entity, ENTITY_ID, TO_BE_DELETED};
 
-pub trait EntityDataTraits<T> {
-    fn insert(&mut self, name: &str, data: T) -> Result<()>;
+#[derive(Debug, Clone, Copy)]
+struct ComponentType {
+    id: TypeId,
+    name: &'static str,
 }
 
+/// Storage for every entity in the world. Entities are grouped into archetypes by the set
+/// of components that they have, so that queries only need to look at the archetypes that
+/// have all of the requested components.
 #[derive(Debug, Default)]
 pub struct EntityData {
-    pub components: HashMap<String, Vec<ComponentData>>,
+    registered: HashMap<String, Option<ComponentType>>,
+    archetypes: Vec<Archetype>,
+    archetype_indexes: HashMap<BTreeSet<String>, usize>,
+    entities: Entities,
 }
 
 impl EntityData {
     pub fn new() -> Self {
-        Self {
-            components: HashMap::new(),
-        }
+        Self::default()
     }
 
     pub fn register(&mut self, name: String) -> Result<()> {
-        if self.components.contains_key(&name) {
-            return Err(BbEcsError::ComponentAlreadyRegistered(name).into());
+        if self.registered.contains_key(&name) {
+            return Err(BbEcsError::ComponentAlreadyRegistered(name));
         }
-        let components = vec![];
-        self.components.insert(name, components);
+        self.registered.insert(name, None);
         Ok(())
     }
 
-    pub fn query(
-        &self,
-        bitmap: BTreeMap<String, &Vec<bool>>,
-    ) -> Result<HashMap<String, Vec<&ComponentData>>> {
-        let mut results = HashMap::new();
-
-        for (name, map) in bitmap.iter() {
-            if let Some(components_list) = self.components.get(name) {
-                let mut missing_components_count = 0;
-                let mut component_results = vec![];
-                for (index, component_map) in map.iter().enumerate() {
-                    if *component_map {
-                        if self.does_entity_have_all_components(&bitmap, index) {
-                            component_results
-                                .push(&components_list[index - missing_components_count]);
-                        }
-                    } else {
-                        missing_components_count += 1;
-                    }
-                }
-                results.insert(name.to_owned(), component_results);
-            } else {
-                return Err(BbEcsError::ComponentNotFound(name.to_owned()).into());
-            }
+    /// Forget a registered name. Nothing should be stored under the name any more.
+    pub fn unregister(&mut self, name: &str) {
+        self.registered.remove(name);
+    }
+
+    /// Register the name with the type already attached, so that only components of that
+    /// type can be stored under it
+    pub fn register_typed<T: Storable>(&mut self, name: String) -> Result<()> {
+        self.register(name.clone())?;
+        self.check_type::<T>(&name)
+    }
+
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.registered.contains_key(name)
+    }
+
+    /// The type stored under the name, once a component has been inserted with it
+    pub fn registered_type(&self, name: &str) -> Option<TypeId> {
+        self.registered
+            .get(name)
+            .copied()
+            .flatten()
+            .map(|component_type| component_type.id)
+    }
+
+    pub fn registered_names(&self) -> impl Iterator<Item = &String> {
+        self.registered.keys()
+    }
+
+    pub fn archetypes(&self) -> &[Archetype] {
+        &self.archetypes
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entities.is_alive(entity)
+    }
+
+    pub fn location(&self, entity: Entity) -> Result<EntityLocation> {
+        if let Some(location) = self.entities.location(entity) {
+            Ok(location)
+        } else {
+            Err(BbEcsError::EntityNotAlive(entity))
         }
+    }
 
-        Ok(results)
+    /// Create a new entity that only has the components every entity is given
+    pub fn spawn(&mut self, change_tick: u32) -> Result<Entity> {
+        let names = [TO_BE_DELETED, ENTITY_ID]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let archetype = self.get_or_create_archetype(names);
+        let entity = self.entities.allocate(EntityLocation {
+            archetype,
+            row: self.archetypes[archetype].len(),
+        });
+
+        self.check_type::<bool>(TO_BE_DELETED)?;
+        self.check_type::<Entity>(ENTITY_ID)?;
+        let mut components = HashMap::new();
+        components.insert(TO_BE_DELETED.to_owned(), ComponentData::new(false));
+        components.insert(ENTITY_ID.to_owned(), ComponentData::new(entity));
+        for component in components.values() {
+            component.ticks().set_added(change_tick);
+        }
+        self.archetypes[archetype].push(entity, components);
+
+        Ok(entity)
     }
 
-    fn does_entity_have_all_components(
-        &self,
-        bitmap: &BTreeMap<String, &Vec<bool>>,
-        entity_index: usize,
-    ) -> bool {
-        for components in bitmap.values() {
-            if !components[entity_index] {
-                return false;
+    /// Spawn a new entity with all of the components at once. Every component is checked
+    /// before anything is changed, so nothing is spawned if any of them cannot be stored.
+    pub fn spawn_bundle(
+        &mut self,
+        bundle: Vec<(String, ComponentData)>,
+        change_tick: u32,
+    ) -> Result<Entity> {
+        let mut names: BTreeSet<String> = [TO_BE_DELETED, ENTITY_ID]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        for (name, component) in &bundle {
+            if name == TO_BE_DELETED || name == ENTITY_ID {
+                return Err(BbEcsError::BuiltInComponent(name.clone()));
+            }
+            if !names.insert(name.clone()) {
+                return Err(BbEcsError::DuplicateComponent(name.clone()));
             }
+            self.type_matches(name, component.type_id(), component.type_name())?;
+        }
+
+        self.check_type::<bool>(TO_BE_DELETED)?;
+        self.check_type::<Entity>(ENTITY_ID)?;
+        for (name, component) in &bundle {
+            self.check_type_id(name, component.type_id(), component.type_name())?;
+        }
+
+        let archetype = self.get_or_create_archetype(names);
+        let entity = self.entities.allocate(EntityLocation {
+            archetype,
+            row: self.archetypes[archetype].len(),
+        });
+
+        let mut components: HashMap<String, ComponentData> = bundle.into_iter().collect();
+        components.insert(TO_BE_DELETED.to_owned(), ComponentData::new(false));
+        components.insert(ENTITY_ID.to_owned(), ComponentData::new(entity));
+        for component in components.values() {
+            component.ticks().set_added(change_tick);
         }
+        self.archetypes[archetype].push(entity, components);
 
-        true
+        Ok(entity)
     }
 
-    pub fn delete_entities_by_index(&mut self, bitmap: HashMap<String, Vec<usize>>) -> Result<()> {
-        for (component_name, mut indexes_to_delete) in bitmap {
-            indexes_to_delete.reverse();
-            if let Some(components) = self.components.get_mut(&component_name) {
-                for index in indexes_to_delete {
-                    components.remove(index);
-                }
-            }
+    /// Inserts the data as a component on the entity, moving the entity to the archetype with
+    /// the new component. Components of any type can be stored, however all of the components
+    /// under one name must be the same type. If the entity already has the component it is
+    /// replaced, which counts as a change rather than the component being added.
+    pub fn insert<T: Storable>(
+        &mut self,
+        entity: Entity,
+        name: &str,
+        data: T,
+        change_tick: u32,
+    ) -> Result<()> {
+        let location = self.location(entity)?;
+        self.check_type::<T>(name)?;
+
+        let mut component = ComponentData::new(data);
+        let archetype = &mut self.archetypes[location.archetype];
+        if let Some(column) = archetype.column_mut(name) {
+            component.set_name(name);
+            let added_tick = column[location.row].ticks().added();
+            component.ticks().set_added(added_tick);
+            component.ticks().set_changed(change_tick);
+            column[location.row] = component;
+            return Ok(());
         }
 
+        let mut names = archetype.names().clone();
+        names.insert(name.to_owned());
+        let mut components = self.take_entity(location);
+        component.ticks().set_added(change_tick);
+        components.insert(name.to_owned(), component);
+        self.place_entity(entity, names, components);
         Ok(())
     }
-}
 
-impl EntityDataTraits<Point> for EntityData {
-    fn insert(&mut self, name: &str, data: Point) -> Result<()> {
-        if let Some(components) = self.components.get_mut(name) {
-            components.push(ComponentData::Point(Rc::new(RefCell::new(data))));
-        } else {
-            return Err(BbEcsError::NeedToRegister.into());
+    /// Removes the component from the entity and moves the data out. Nothing is removed if
+    /// the data is not of type T or is still in use.
+    pub fn remove<T: Storable>(&mut self, entity: Entity, name: &str) -> Result<T> {
+        let location = self.location(entity)?;
+        let archetype = &self.archetypes[location.archetype];
+        let component = match archetype.column(name) {
+            Some(column) => &column[location.row],
+            None => return Err(BbEcsError::EntityMissingComponent(entity, name.to_owned())),
+        };
+        let wrapper: &DataWrapper<T> = component.cast()?;
+        if data_wrapper::is_shared(wrapper) {
+            return Err(BbEcsError::ComponentInUse(component.type_name()));
         }
-        Ok(())
+
+        let mut names = archetype.names().clone();
+        names.remove(name);
+        let mut components = self.take_entity(location);
+        let removed = components
+            .remove(name)
+            .expect("component was in the archetype");
+        self.place_entity(entity, names, components);
+        removed.into_inner()
+    }
+
+    pub fn has_component(&self, entity: Entity, name: &str) -> Result<bool> {
+        let location = self.location(entity)?;
+        Ok(self.archetypes[location.archetype].has_component(name))
     }
-}
 
-impl EntityDataTraits<Color> for EntityData {
-    fn insert(&mut self, name: &str, data: Color) -> Result<()> {
-        if let Some(components) = self.components.get_mut(name) {
-            components.push(ComponentData::Color(Rc::new(RefCell::new(data))));
+    pub fn get(&self, entity: Entity, name: &str) -> Result<&ComponentData> {
+        let location = self.location(entity)?;
+        if let Some(column) = self.archetypes[location.archetype].column(name) {
+            Ok(&column[location.row])
         } else {
-            return Err(BbEcsError::NeedToRegister.into());
+            Err(BbEcsError::EntityMissingComponent(entity, name.to_owned()))
         }
-        Ok(())
     }
-}
 
-impl EntityDataTraits<f32> for EntityData {
-    fn insert(&mut self, name: &str, data: f32) -> Result<()> {
-        if let Some(components) = self.components.get_mut(name) {
-            components.push(ComponentData::F32(Rc::new(RefCell::new(data))));
-        } else {
-            return Err(BbEcsError::NeedToRegister.into());
+    /// The names of every component that the entity has
+    pub fn component_names(&self, entity: Entity) -> Result<&BTreeSet<String>> {
+        let location = self.location(entity)?;
+        Ok(self.archetypes[location.archetype].names())
+    }
+
+    /// Every component that the entity has, along with its name
+    pub fn components(&self, entity: Entity) -> Result<Vec<(&String, &ComponentData)>> {
+        let location = self.location(entity)?;
+        Ok(self.archetypes[location.archetype]
+            .columns()
+            .map(|(name, column)| (name, &column[location.row]))
+            .collect())
+    }
+
+    pub fn query(&self, names: &[&str], filter: &QueryFilter) -> Result<QueryResults<'_>> {
+        for name in names.iter().copied().chain(filter.names()) {
+            if !self.registered.contains_key(name) {
+                return Err(BbEcsError::ComponentNotFound(name.to_owned()));
+            }
         }
-        Ok(())
+
+        let mut results = QueryResults::new(names, filter.optional_names());
+        for archetype in &self.archetypes {
+            if archetype.is_empty()
+                || !archetype.has_all_components(names)
+                || !filter.matches_archetype(archetype)
+            {
+                continue;
+            }
+
+            let mut rows = Self::rows_not_flagged(archetype)?;
+            rows.retain(|row| filter.matches_row(archetype, *row));
+            results.extend(archetype, &rows);
+        }
+
+        Ok(results)
     }
-}
 
-impl EntityDataTraits<Mesh> for EntityData {
-    fn insert(&mut self, name: &str, data: Mesh) -> Result<()> {
-        if let Some(components) = self.components.get_mut(name) {
-            components.push(ComponentData::Mesh(Rc::new(RefCell::new(data))));
-        } else {
-            return Err(BbEcsError::NeedToRegister.into());
+    /// Entities that are flagged to be deleted are left out of queries, even though they are
+    /// not removed until the next update.
+    fn rows_not_flagged(archetype: &Archetype) -> Result<Vec<usize>> {
+        let mut rows = vec![];
+        if let Some(flags) = archetype.column(TO_BE_DELETED) {
+            for (row, flag) in flags.iter().enumerate() {
+                let wrapped_flag: &DataWrapper<bool> = flag.cast()?;
+                if !*wrapped_flag.borrow() {
+                    rows.push(row);
+                }
+            }
         }
-        Ok(())
+        Ok(rows)
     }
-}
 
-impl EntityDataTraits<u32> for EntityData {
-    fn insert(&mut self, name: &str, data: u32) -> Result<()> {
-        if let Some(components) = self.components.get_mut(name) {
-            components.push(ComponentData::U32(Rc::new(RefCell::new(data))));
-        } else {
-            return Err(BbEcsError::NeedToRegister.into());
+    /// Remove the entity from storage straight away
+    pub fn despawn(&mut self, entity: Entity, strategy: DeletionStrategy) -> Result<()> {
+        let location = self.location(entity)?;
+        let archetype = &mut self.archetypes[location.archetype];
+        let (_, first_changed_row) = archetype.remove_rows(&[location.row], strategy);
+
+        for row in first_changed_row..archetype.len() {
+            self.entities.set_location(
+                archetype.entities()[row],
+                EntityLocation {
+                    archetype: location.archetype,
+                    row,
+                },
+            );
         }
+        self.entities.free(entity);
         Ok(())
     }
-}
 
-impl EntityDataTraits<usize> for EntityData {
-    fn insert(&mut self, name: &str, data: usize) -> Result<()> {
-        if let Some(components) = self.components.get_mut(name) {
-            components.push(ComponentData::Usize(Rc::new(RefCell::new(data))));
-        } else {
-            return Err(BbEcsError::NeedToRegister.into());
+    /// Remove every entity that has been flagged to be deleted, returning the entities that
+    /// were removed along with the index of the archetype each was removed from.
+    pub fn delete_flagged(&mut self, strategy: DeletionStrategy) -> Result<Vec<(Entity, usize)>> {
+        let mut deleted_entities = vec![];
+
+        for (archetype_index, archetype) in self.archetypes.iter_mut().enumerate() {
+            let mut rows_to_delete = vec![];
+            if let Some(flags) = archetype.column(TO_BE_DELETED) {
+                for (row, flag) in flags.iter().enumerate() {
+                    let wrapped_flag: &DataWrapper<bool> = flag.cast()?;
+                    if *wrapped_flag.borrow() {
+                        rows_to_delete.push(row);
+                    }
+                }
+            }
+
+            if rows_to_delete.is_empty() {
+                continue;
+            }
+
+            let (removed_entities, first_changed_row) =
+                archetype.remove_rows(&rows_to_delete, strategy);
+            deleted_entities.extend(
+                removed_entities
+                    .into_iter()
+                    .map(|entity| (entity, archetype_index)),
+            );
+
+            for row in first_changed_row..archetype.len() {
+                self.entities.set_location(
+                    archetype.entities()[row],
+                    EntityLocation {
+                        archetype: archetype_index,
+                        row,
+                    },
+                );
+            }
         }
-        Ok(())
+
+        for (entity, _) in &deleted_entities {
+            self.entities.free(*entity);
+        }
+
+        Ok(deleted_entities)
     }
-}
 
-impl EntityDataTraits<bool> for EntityData {
-    fn insert(&mut self, name: &str, data: bool) -> Result<()> {
-        if let Some(components) = self.components.get_mut(name) {
-            components.push(ComponentData::Bool(Rc::new(RefCell::new(data))));
-        } else {
-            return Err(BbEcsError::NeedToRegister.into());
+    pub fn entities(&self) -> &Entities {
+        &self.entities
+    }
+
+    /// Take every entity out of storage along with its components. The entities are still
+    /// alive, but have nowhere to live until `restore` puts them back.
+    pub fn take_all(&mut self) -> Vec<(Entity, HashMap<String, ComponentData>)> {
+        self.archetypes
+            .iter_mut()
+            .flat_map(Archetype::take_all)
+            .collect()
+    }
+
+    /// Replace the state of the entity allocator, and put the entities into storage with
+    /// their components. Every component name must already be registered.
+    pub fn restore(
+        &mut self,
+        generations: Vec<u32>,
+        free_indexes: Vec<u32>,
+        entities: Vec<(Entity, HashMap<String, ComponentData>)>,
+    ) -> Result<()> {
+        self.entities.restore(generations, free_indexes);
+
+        for (entity, components) in entities {
+            let names: BTreeSet<String> = components.keys().cloned().collect();
+            if let Some(name) = names.iter().find(|name| !self.is_registered(name)) {
+                return Err(BbEcsError::ComponentNotFound(name.clone()));
+            }
+
+            let archetype = self.get_or_create_archetype(names);
+            let row = self.archetypes[archetype].push(entity, components);
+            self.entities
+                .revive(entity, EntityLocation { archetype, row });
         }
         Ok(())
     }
-}
 
-impl EntityDataTraits<KeyCode> for EntityData {
-    fn insert(&mut self, name: &str, data: KeyCode) -> Result<()> {
-        if let Some(components) = self.components.get_mut(name) {
-            components.push(ComponentData::GgezKeyCode(Rc::new(RefCell::new(data))));
-        } else {
-            return Err(BbEcsError::NeedToRegister.into());
+    fn check_type<T: Storable>(&mut self, name: &str) -> Result<()> {
+        self.check_type_id(name, TypeId::of::<T>(), std::any::type_name::<T>())
+    }
+
+    /// Make sure components of the type can be stored under the name, remembering the type if
+    /// it is the first to be stored there
+    fn check_type_id(&mut self, name: &str, id: TypeId, type_name: &'static str) -> Result<()> {
+        self.type_matches(name, id, type_name)?;
+        if let Some(component_type @ None) = self.registered.get_mut(name) {
+            *component_type = Some(ComponentType {
+                id,
+                name: type_name,
+            });
         }
         Ok(())
     }
-}
 
-impl EntityDataTraits<String> for EntityData {
-    fn insert(&mut self, name: &str, data: String) -> Result<()> {
-        if let Some(components) = self.components.get_mut(name) {
-            components.push(ComponentData::Marker(Rc::new(RefCell::new(data))));
-        } else {
-            return Err(BbEcsError::NeedToRegister.into());
+    pub fn type_matches(&self, name: &str, id: TypeId, type_name: &'static str) -> Result<()> {
+        match self.registered.get(name) {
+            Some(Some(component_type)) if component_type.id != id => Err(
+                BbEcsError::ComponentTypeMismatch(name.to_owned(), component_type.name, type_name),
+            ),
+            Some(_) => Ok(()),
+            None => Err(BbEcsError::NeedToRegister),
         }
-        Ok(())
     }
-}
 
-impl EntityDataTraits<Text> for EntityData {
-    fn insert(&mut self, name: &str, data: Text) -> Result<()> {
-        if let Some(components) = self.components.get_mut(name) {
-            components.push(ComponentData::GgezText(Rc::new(RefCell::new(data))));
-        } else {
-            return Err(BbEcsError::NeedToRegister.into());
+    fn get_or_create_archetype(&mut self, names: BTreeSet<String>) -> usize {
+        if let Some(index) = self.archetype_indexes.get(&names) {
+            return *index;
         }
-        Ok(())
+
+        let index = self.archetypes.len();
+        self.archetypes.push(Archetype::new(names.clone()));
+        self.archetype_indexes.insert(names, index);
+        index
     }
-}
 
-impl EntityDataTraits<ggez::audio::SoundData> for EntityData {
-    fn insert(&mut self, name: &str, data: ggez::audio::SoundData) -> Result<()> {
-        if let Some(components) = self.components.get_mut(name) {
-            components.push(ComponentData::GgezSound(Rc::new(RefCell::new(data))));
-        } else {
-            return Err(BbEcsError::NeedToRegister.into());
+    /// Take all of the components of the entity out of its archetype. The entity will need
+    /// to be placed into another archetype afterwards.
+    fn take_entity(&mut self, location: EntityLocation) -> HashMap<String, ComponentData> {
+        let (components, moved_entity) =
+            self.archetypes[location.archetype].swap_remove(location.row);
+        if let Some(moved_entity) = moved_entity {
+            self.entities.set_location(moved_entity, location);
         }
-        Ok(())
+        components
+    }
+
+    fn place_entity(
+        &mut self,
+        entity: Entity,
+        names: BTreeSet<String>,
+        components: HashMap<String, ComponentData>,
+    ) {
+        let archetype = self.get_or_create_archetype(names);
+        let row = self.archetypes[archetype].push(entity, components);
+        self.entities
+            .set_location(entity, EntityLocation { archetype, row });
     }
 }
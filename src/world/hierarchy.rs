@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use super::Entity;
+
+/// The parent and children of every entity that is part of a hierarchy. Entities that have
+/// no parent and no children are not stored.
+#[derive(Debug, Default)]
+pub struct Hierarchy {
+    parents: HashMap<Entity, Entity>,
+    children: HashMap<Entity, Vec<Entity>>,
+}
+
+impl Hierarchy {
+    pub fn parent(&self, entity: Entity) -> Option<Entity> {
+        self.parents.get(&entity).copied()
+    }
+
+    pub fn children(&self, entity: Entity) -> &[Entity] {
+        self.children.get(&entity).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every entity below this one in the hierarchy, with each parent before its children
+    pub fn descendants(&self, entity: Entity) -> Vec<Entity> {
+        let mut descendants = vec![];
+        let mut to_visit: Vec<Entity> = self.children(entity).iter().rev().copied().collect();
+
+        while let Some(descendant) = to_visit.pop() {
+            descendants.push(descendant);
+            to_visit.extend(self.children(descendant).iter().rev());
+        }
+
+        descendants
+    }
+
+    pub fn is_descendant(&self, entity: Entity, ancestor: Entity) -> bool {
+        std::iter::successors(self.parent(entity), |entity| self.parent(*entity))
+            .any(|parent| parent == ancestor)
+    }
+
+    /// Move the child under the new parent, taking it away from any parent it had before
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) {
+        self.remove_parent(child);
+        self.parents.insert(child, parent);
+        self.children.entry(parent).or_default().push(child);
+    }
+
+    pub fn remove_parent(&mut self, child: Entity) -> Option<Entity> {
+        let parent = self.parents.remove(&child)?;
+        if let Some(siblings) = self.children.get_mut(&parent) {
+            siblings.retain(|sibling| *sibling != child);
+            if siblings.is_empty() {
+                self.children.remove(&parent);
+            }
+        }
+        Some(parent)
+    }
+
+    /// Take an entity that has been removed from the world out of the hierarchy. Its
+    /// children are left without a parent.
+    pub fn remove(&mut self, entity: Entity) {
+        self.remove_parent(entity);
+        for child in self.children.remove(&entity).unwrap_or_default() {
+            self.parents.remove(&child);
+        }
+    }
+}
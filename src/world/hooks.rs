@@ -0,0 +1,58 @@
+use std::collections::{BTreeSet, HashMap};
+
+use super::commands::Commands;
+use super::Entity;
+
+#[cfg(not(feature = "parallel"))]
+pub(crate) type Hook = Box<dyn Fn(Entity, &mut Commands)>;
+#[cfg(feature = "parallel")]
+pub(crate) type Hook = Box<dyn Fn(Entity, &mut Commands) + Send + Sync>;
+
+/// The moments in a component's life that hooks can be registered for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HookEvent {
+    Insert,
+    Remove,
+    Despawn,
+}
+
+/// Hooks registered on the world by component name
+#[derive(Default)]
+pub(crate) struct Hooks {
+    on_insert: HashMap<String, Vec<Hook>>,
+    on_remove: HashMap<String, Vec<Hook>>,
+    on_despawn: HashMap<String, Vec<Hook>>,
+}
+
+impl Hooks {
+    pub fn add(&mut self, event: HookEvent, name: &str, hook: Hook) {
+        let hooks = match event {
+            HookEvent::Insert => &mut self.on_insert,
+            HookEvent::Remove => &mut self.on_remove,
+            HookEvent::Despawn => &mut self.on_despawn,
+        };
+        hooks.entry(name.to_owned()).or_default().push(hook);
+    }
+
+    /// Run every hook for the event on the component, in the order they were added
+    pub fn run(&self, event: HookEvent, name: &str, entity: Entity, commands: &mut Commands) {
+        let hooks = match event {
+            HookEvent::Insert => &self.on_insert,
+            HookEvent::Remove => &self.on_remove,
+            HookEvent::Despawn => &self.on_despawn,
+        };
+        for hook in hooks.get(name).into_iter().flatten() {
+            hook(entity, commands);
+        }
+    }
+
+    /// Run the despawn hooks for every component the entity had
+    pub fn run_despawn(&self, entity: Entity, names: &BTreeSet<String>, commands: &mut Commands) {
+        if self.on_despawn.is_empty() {
+            return;
+        }
+        for name in names {
+            self.run(HookEvent::Despawn, name, entity, commands);
+        }
+    }
+}
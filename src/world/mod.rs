@@ -1,47 +1,65 @@
-pub mod bitmap;
+mod archetype;
+pub mod bundle;
+pub mod commands;
+pub mod data_wrapper;
+mod entity;
+mod entity_builder;
 mod entity_data;
+mod hierarchy;
+mod hooks;
+#[cfg(feature = "serialize")]
+pub mod prefab;
+pub mod query;
+#[cfg(feature = "serialize")]
+pub mod serialize;
+pub mod snapshot;
 
-use std::cell::RefCell;
 use std::collections::HashMap;
-use std::rc::Rc;
 
 use entity_data::EntityData;
-use eyre::Result;
-use ggez::audio::SoundData;
-use ggez::event::KeyCode;
-use ggez::graphics::{Color, Mesh, Text};
-
-use crate::components::{CastComponents, ComponentData};
-use crate::data_types::point::Point;
-use crate::resources::resource::Resource;
+
+use crate::components::{CastComponents, Component, ComponentData};
+use crate::data_types::transform::Transform;
+use crate::errors::{BbEcsError, Result};
+use crate::resources::events::{self, Events};
+use crate::resources::resource::{Resource, ResourceCast};
 use crate::resources::resources_data::ResourcesData;
+use crate::resources::NamedResource;
 
-use self::bitmap::BitMap;
-use self::entity_data::EntityDataTraits;
+use self::bundle::Bundle;
+use self::commands::{CommandError, Commands};
+pub use self::entity::Entity;
+pub use self::entity_builder::EntityBuilder;
+use self::hierarchy::Hierarchy;
+use self::hooks::{HookEvent, Hooks};
+#[cfg(feature = "serialize")]
+use self::prefab::{PrefabValue, Prefabs};
+use self::query::{Mut, QueryData, QueryFilter, QueryResults, TypedQuery};
 
-macro_rules! impl_world_trait {
-    ($new_type:ty, $arm:ident) => {
-        impl WorldMethods<$new_type> for World {
-            fn with_component(&mut self, name: &str, data: $new_type) -> Result<&mut Self> {
-                self.entity_data.insert(name, data)?;
-                self.bitmap.insert(name)?;
-                Ok(self)
-            }
+pub(crate) const TO_BE_DELETED: &str = "to be deleted";
+pub const ENTITY_ID: &str = "entity id";
+/// A `Transform` relative to the entity's parent, or to the world if it has no parent
+pub const LOCAL_TRANSFORM: &str = "local transform";
+/// A `Transform` relative to the world, worked out from the local transforms in the hierarchy
+pub const GLOBAL_TRANSFORM: &str = "global transform";
 
-            fn add_resource(&mut self, name: String, data: $new_type) {
-                self.resources.insert(name, Resource::$arm(data));
-            }
-        }
-    };
-}
+pub use self::data_wrapper::{DataRef, DataRefMut, DataWrapper, Storable};
 
-const TO_BE_DELETED: &str = "to be deleted";
-pub const ENTITY_ID: &str = "entity id";
+/// How entities are removed from storage when `World::update` deletes them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeletionStrategy {
+    /// The remaining entities keep the order that they were in, so queries return them in
+    /// the same order as before
+    #[default]
+    PreserveOrder,
+    /// The last entities are moved into the gaps left by deleted entities. This does less
+    /// work when only a few entities are deleted, but changes the order of query results.
+    SwapRemove,
+}
 
-pub type DataWrapper<T> = Rc<RefCell<T>>;
+type EventsUpdater = fn(&World, &str) -> Result<()>;
 
 pub trait WorldMethods<T> {
-    fn with_component(&mut self, name: &str, data: T) -> Result<&mut Self>;
     fn add_resource(&mut self, name: String, data: T);
 }
 
@@ -49,8 +67,14 @@ pub struct World {
     pub entity_data: EntityData,
     resources: ResourcesData,
     is_empty: bool,
-    next_entity_id: u32,
-    bitmap: BitMap,
+    deletion_strategy: DeletionStrategy,
+    queued_commands: Commands,
+    events_updaters: HashMap<String, EventsUpdater>,
+    change_tick: u32,
+    hierarchy: Hierarchy,
+    hooks: Hooks,
+    #[cfg(feature = "serialize")]
+    prefabs: Prefabs,
 }
 
 impl World {
@@ -58,76 +82,711 @@ impl World {
         Self::default()
     }
 
+    /// Register a component name so that components can be inserted with it. The transform
+    /// names are registered by the world, so registering them again does nothing.
     pub fn register<S: ToString>(&mut self, name: S) -> Result<()> {
-        self.entity_data.register(name.to_string())?;
-        self.bitmap.register(name.to_string());
-        Ok(())
+        let name = name.to_string();
+        if name == LOCAL_TRANSFORM || name == GLOBAL_TRANSFORM {
+            return Ok(());
+        }
+        self.entity_data.register(name)
     }
 
-    pub fn spawn_entity(&mut self) -> Result<&mut Self> {
-        self.entity_data.insert(TO_BE_DELETED, false)?;
-        self.entity_data.insert(ENTITY_ID, self.next_entity_id)?;
-        self.bitmap.spawn_entity();
-        self.bitmap.insert(TO_BE_DELETED)?;
-        self.bitmap.insert(ENTITY_ID)?;
+    /// Register the component under the name for its type
+    pub fn register_component<T: Component>(&mut self) -> Result<()> {
+        self.register(T::NAME)
+    }
+
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.entity_data.is_registered(name)
+    }
+
+    /// Spawn a new entity, returning a builder that components can be chained onto. The
+    /// entity handle can be retrieved from the builder with `entity()`.
+    pub fn spawn_entity(&mut self) -> Result<EntityBuilder<'_>> {
+        let entity = self.entity_data.spawn(self.change_tick)?;
+        self.is_empty = false;
+
+        Ok(EntityBuilder::new(self, entity))
+    }
+
+    /// Spawn a new entity with every component in the bundle. The components are checked
+    /// before the entity is spawned, so if any of them cannot be stored nothing is spawned.
+    /// Like `spawn_entity`, this returns a builder that more components can be chained onto.
+    ///
+    /// ```
+    /// use bbecs::data_types::point::Point;
+    /// use bbecs::world::World;
+    ///
+    /// let mut world = World::new();
+    /// world.register("location").unwrap();
+    /// world.register("velocity").unwrap();
+    ///
+    /// world
+    ///     .spawn_bundle((("location", Point::new(0.0, 0.0)), ("velocity", Point::new(1.0, 0.0))))
+    ///     .unwrap();
+    /// assert!(world.spawn_bundle((("location", Point::new(0.0, 0.0)), ("size", 5.0_f32))).is_err());
+    /// assert_eq!(world.query_typed::<(&Point,)>(["location"]).unwrap().len(), 1);
+    /// ```
+    pub fn spawn_bundle<B: Bundle>(&mut self, bundle: B) -> Result<EntityBuilder<'_>> {
+        let components = bundle.into_components();
+        let names: Vec<String> = components.iter().map(|(name, _)| name.clone()).collect();
+        let entity = self
+            .entity_data
+            .spawn_bundle(components, self.change_tick)?;
         self.is_empty = false;
-        self.next_entity_id += 1;
+        for name in names {
+            self.run_hooks(HookEvent::Insert, &name, entity);
+        }
+
+        Ok(EntityBuilder::new(self, entity))
+    }
+
+    /// Check if the entity handle still refers to an entity in the world. Handles to deleted
+    /// entities are never alive again, even once their index has been reused.
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entity_data.is_alive(entity)
+    }
+
+    /// Add a component to any entity that is alive in the world. If the entity already has a
+    /// component with this name then it is replaced with the new data.
+    ///
+    /// ```
+    /// use bbecs::world::World;
+    ///
+    /// let mut world = World::new();
+    /// world.register("stunned").unwrap();
+    /// let first = world.spawn_entity().unwrap().entity();
+    /// world.spawn_entity().unwrap();
+    ///
+    /// world.insert_component(first, "stunned", true).unwrap();
+    /// assert!(world.has_component(first, "stunned").unwrap());
+    /// ```
+    pub fn insert_component<T: Storable>(
+        &mut self,
+        entity: Entity,
+        name: &str,
+        data: T,
+    ) -> Result<()> {
+        if name == TO_BE_DELETED || name == ENTITY_ID {
+            return Err(BbEcsError::BuiltInComponent(name.to_owned()));
+        }
+
+        self.entity_data
+            .insert(entity, name, data, self.change_tick)?;
+        self.run_hooks(HookEvent::Insert, name, entity);
+        Ok(())
+    }
+
+    /// Take a component off of an entity, returning the data that was stored in it.
+    ///
+    /// ```
+    /// use bbecs::world::World;
+    ///
+    /// let mut world = World::new();
+    /// world.register("on fire").unwrap();
+    /// let entity = world
+    ///     .spawn_entity()
+    ///     .unwrap()
+    ///     .with_component("on fire", 3.0_f32)
+    ///     .unwrap()
+    ///     .entity();
+    ///
+    /// let burn_time: f32 = world.remove_component(entity, "on fire").unwrap();
+    /// assert_eq!(burn_time, 3.0);
+    /// assert!(!world.has_component(entity, "on fire").unwrap());
+    /// ```
+    pub fn remove_component<T: Storable>(&mut self, entity: Entity, name: &str) -> Result<T> {
+        if name == TO_BE_DELETED || name == ENTITY_ID {
+            return Err(BbEcsError::BuiltInComponent(name.to_owned()));
+        }
+
+        let removed = self.entity_data.remove(entity, name)?;
+        self.run_hooks(HookEvent::Remove, name, entity);
+        Ok(removed)
+    }
+
+    /// Check if the entity currently has a component with the given name
+    pub fn has_component(&self, entity: Entity, name: &str) -> Result<bool> {
+        self.entity_data.has_component(entity, name)
+    }
 
-        Ok(self)
+    /// Borrow a component of an entity. This fails instead of panicking when the component
+    /// is already mutably borrowed, for example by a typed query that is still running.
+    ///
+    /// ```
+    /// use bbecs::errors::{BbEcsError, BorrowAccess};
+    /// use bbecs::world::World;
+    ///
+    /// let mut world = World::new();
+    /// world.register("health").unwrap();
+    /// let entity = world
+    ///     .spawn_entity()
+    ///     .unwrap()
+    ///     .with_component("health", 10_u32)
+    ///     .unwrap()
+    ///     .entity();
+    ///
+    /// let health = world.try_borrow_component_mut::<u32>(entity, "health").unwrap();
+    /// let error = world.try_borrow_component::<u32>(entity, "health").unwrap_err();
+    /// assert!(matches!(
+    ///     error,
+    ///     BbEcsError::BorrowConflict(name, BorrowAccess::Read) if name == "health"
+    /// ));
+    ///
+    /// drop(health);
+    /// assert_eq!(*world.try_borrow_component::<u32>(entity, "health").unwrap(), 10);
+    /// ```
+    pub fn try_borrow_component<T: Storable>(
+        &self,
+        entity: Entity,
+        name: &str,
+    ) -> Result<DataRef<'_, T>> {
+        let wrapper = self.entity_data.get(entity, name)?.cast()?;
+        data_wrapper::try_borrow(wrapper, name)
+    }
+
+    /// Mutably borrow a component of an entity, failing instead of panicking when the
+    /// component is already borrowed. Like a typed query, the component is marked as changed
+    /// when it is mutably dereferenced.
+    pub fn try_borrow_component_mut<T: Storable>(
+        &self,
+        entity: Entity,
+        name: &str,
+    ) -> Result<Mut<'_, T>> {
+        let component = self.entity_data.get(entity, name)?;
+        let wrapper = component.cast()?;
+        Mut::try_new(wrapper, name, component.ticks(), self.change_tick)
     }
 
     pub fn query(&self, names: Vec<&str>) -> Result<HashMap<String, Vec<&ComponentData>>> {
-        let bitmap_query = self.bitmap.query(names.clone())?;
-        self.entity_data.query(bitmap_query)
+        Ok(self
+            .entity_data
+            .query(&names, &QueryFilter::default())?
+            .into_components())
     }
 
-    pub fn get_resource<S: Into<String>>(&self, name: S) -> Result<&Rc<RefCell<Resource>>> {
+    /// Query for components, only returning the entities that match the filter. Components
+    /// that the filter marks as optional are returned for every entity, with `None` for the
+    /// entities that do not have them.
+    ///
+    /// ```
+    /// use bbecs::data_types::point::Point;
+    /// use bbecs::world::query::QueryFilter;
+    /// use bbecs::world::World;
+    ///
+    /// let mut world = World::new();
+    /// world.register("location").unwrap();
+    /// world.register("size").unwrap();
+    /// world.register("frozen").unwrap();
+    /// world
+    ///     .spawn_entity()
+    ///     .unwrap()
+    ///     .with_component("location", Point::new(0.0, 0.0))
+    ///     .unwrap()
+    ///     .with_component("size", 5.0_f32)
+    ///     .unwrap();
+    /// world
+    ///     .spawn_entity()
+    ///     .unwrap()
+    ///     .with_component("location", Point::new(1.0, 0.0))
+    ///     .unwrap();
+    /// world
+    ///     .spawn_entity()
+    ///     .unwrap()
+    ///     .with_component("location", Point::new(2.0, 0.0))
+    ///     .unwrap()
+    ///     .with_component("frozen", true)
+    ///     .unwrap();
+    ///
+    /// let filter = QueryFilter::new().optional("size").without("frozen");
+    /// let results = world.query_filtered(vec!["location"], &filter).unwrap();
+    /// assert_eq!(results.len(), 2);
+    /// let sizes = results.get_optional("size").unwrap();
+    /// assert_eq!(sizes.iter().filter(|size| size.is_some()).count(), 1);
+    /// ```
+    pub fn query_filtered(
+        &self,
+        names: Vec<&str>,
+        filter: &QueryFilter,
+    ) -> Result<QueryResults<'_>> {
+        self.entity_data.query(&names, filter)
+    }
+
+    /// Query for components with their types known up front. Each name is matched with the
+    /// type in the same position of the tuple, and the types are checked once when the
+    /// query is created. Iterating gives a tuple per entity with the components borrowed.
+    ///
+    /// ```
+    /// use bbecs::data_types::point::Point;
+    /// use bbecs::world::World;
+    ///
+    /// let mut world = World::new();
+    /// world.register("location").unwrap();
+    /// world.register("size").unwrap();
+    /// world
+    ///     .spawn_entity()
+    ///     .unwrap()
+    ///     .with_component("location", Point::new(0.0, 0.0))
+    ///     .unwrap()
+    ///     .with_component("size", 15.0_f32)
+    ///     .unwrap();
+    ///
+    /// for (location, mut size) in world
+    ///     .query_typed::<(&Point, &mut f32)>(["location", "size"])
+    ///     .unwrap()
+    /// {
+    ///     *size += location.x + 1.0;
+    /// }
+    ///
+    /// let (size,) = world.query_typed::<(&f32,)>(["size"]).unwrap().next().unwrap();
+    /// assert_eq!(*size, 16.0);
+    /// ```
+    pub fn query_typed<'w, Q: QueryData<'w>>(
+        &'w self,
+        names: impl AsRef<[&'w str]>,
+    ) -> Result<TypedQuery<'w, Q>> {
+        self.query_typed_filtered(names, &QueryFilter::default())
+    }
+
+    /// A typed query that only returns the entities that match the filter
+    pub fn query_typed_filtered<'w, Q: QueryData<'w>>(
+        &'w self,
+        names: impl AsRef<[&'w str]>,
+        filter: &QueryFilter,
+    ) -> Result<TypedQuery<'w, Q>> {
+        let names = names.as_ref();
+        let optional_names = Q::optional_names(names);
+        let required_names = names
+            .iter()
+            .copied()
+            .filter(|name| !optional_names.contains(name))
+            .collect();
+        let filter = optional_names
+            .iter()
+            .fold(filter.clone(), |filter, name| filter.optional(name));
+
+        let results = self.query_filtered(required_names, &filter)?;
+        TypedQuery::new(&results, names, self.change_tick)
+    }
+
+    /// The current change tick of the world. Components that are added or changed are
+    /// stamped with this tick, so systems can remember it to find what has changed since
+    /// they last ran.
+    pub fn change_tick(&self) -> u32 {
+        self.change_tick
+    }
+
+    /// Move on to the next change tick, returning the new tick. This is called by `update`,
+    /// and by the schedule before each system runs so that systems see the changes made by
+    /// the systems that ran after them.
+    pub fn increment_change_tick(&mut self) -> u32 {
+        self.change_tick += 1;
+        self.change_tick
+    }
+
+    pub fn get_resource<S: Into<String>>(&self, name: S) -> Result<&DataWrapper<Resource>> {
         self.resources.get(&name.into())
     }
 
+    /// Borrow a resource, failing instead of panicking when it is already mutably borrowed.
+    /// The `try_get_resource!` macro also casts the resource to its type.
+    pub fn try_borrow_resource<S: Into<String>>(&self, name: S) -> Result<DataRef<'_, Resource>> {
+        let name = name.into();
+        data_wrapper::try_borrow(self.resources.get(&name)?, &name)
+    }
+
+    /// Mutably borrow a resource, failing instead of panicking when it is already borrowed.
+    /// The `try_get_resource_mut!` macro also casts the resource to its type.
+    pub fn try_borrow_resource_mut<S: Into<String>>(
+        &self,
+        name: S,
+    ) -> Result<DataRefMut<'_, Resource>> {
+        let name = name.into();
+        data_wrapper::try_borrow_mut(self.resources.get(&name)?, &name)
+    }
+
+    /// Add a resource under the name for its type, replacing any resource with that name
+    pub fn add_named_resource<T: NamedResource>(&mut self, data: T) {
+        self.resources
+            .insert(T::NAME.to_owned(), Resource::new(data));
+    }
+
+    /// Add an `Events` channel as a resource. The event buffers are swapped every time
+    /// `update` is called, so events are kept for two updates. Adding events with a name
+    /// that is already in use replaces the existing resource.
+    ///
+    /// ```
+    /// use bbecs::resources::events::{EventReader, Events};
+    /// use bbecs::resources::resource::ResourceCast;
+    /// use bbecs::world::World;
+    ///
+    /// let mut world = World::new();
+    /// world.add_events::<u32>("points scored");
+    /// world.send_event("points scored", 10_u32).unwrap();
+    ///
+    /// let mut reader = EventReader::new();
+    /// let wrapper = world.get_resource("points scored").unwrap().borrow();
+    /// let events: &Events<u32> = wrapper.cast().unwrap();
+    /// assert_eq!(reader.read(events).sum::<u32>(), 10);
+    /// ```
+    pub fn add_events<T: Storable>(&mut self, name: &str) {
+        self.resources
+            .insert(name.to_owned(), Resource::new(Events::<T>::new()));
+        self.events_updaters
+            .insert(name.to_owned(), events::update_events::<T>);
+    }
+
+    /// Send an event into the `Events` resource with the given name. Sending fails instead of
+    /// panicking if the events are already borrowed.
+    pub fn send_event<T: Storable>(&self, name: &str, event: T) -> Result<()> {
+        let mut wrapper = self.try_borrow_resource_mut(name)?;
+        let events: &mut Events<T> = wrapper.cast_mut().map_err(|error| error.with_name(name))?;
+        events.send(event);
+        Ok(())
+    }
+
+    /// Apply any queued commands, delete the entities that have been flagged to be deleted,
+    /// propagate transforms, swap the buffers of every `Events` resource and move on to the
+    /// next change tick. If any of the commands failed, or transforms or events could not be
+    /// updated, the rest of the update still happens and the error is returned afterwards,
+    /// with the errors for failed commands returned first.
     pub fn update(&mut self) -> Result<()> {
-        let query_results = self.query(vec![TO_BE_DELETED])?;
-        let to_be_deleted_query = query_results.get(TO_BE_DELETED).unwrap();
-        let mut bitmap_indexes_to_delete = vec![];
+        let queued_commands = std::mem::take(&mut self.queued_commands);
+        let command_errors = self.apply_commands(queued_commands);
 
-        to_be_deleted_query
-            .iter()
-            .enumerate()
-            .for_each(|(index, to_be_deleted)| {
-                let to_be_deleted: &Rc<RefCell<bool>> = to_be_deleted.cast().unwrap();
-                let to_be_deleted = to_be_deleted.borrow();
-                if *to_be_deleted {
-                    bitmap_indexes_to_delete.push(index);
-                }
-            });
-
-        let component_indexes_to_delete = self
-            .bitmap
-            .calculate_component_indexes_to_delete(&bitmap_indexes_to_delete)?;
-
-        self.bitmap
-            .delete_entities_by_index(bitmap_indexes_to_delete)?;
+        for (entity, archetype) in self.entity_data.delete_flagged(self.deletion_strategy)? {
+            let names = self.entity_data.archetypes()[archetype].names();
+            self.hooks
+                .run_despawn(entity, names, &mut self.queued_commands);
+            self.hierarchy.remove(entity);
+        }
+        let propagated = self.propagate_transforms();
+        let mut events_updated = Ok(());
+        for (name, update_events) in &self.events_updaters {
+            events_updated = events_updated.and(update_events(self, name));
+        }
+        self.increment_change_tick();
 
-        self.entity_data
-            .delete_entities_by_index(component_indexes_to_delete)?;
+        if !command_errors.is_empty() {
+            return Err(BbEcsError::CommandsFailed(command_errors));
+        }
+        propagated.and(events_updated)
+    }
+
+    /// Apply the commands to the world immediately, returning an error for each command that
+    /// could not be applied.
+    pub fn apply_commands(&mut self, commands: Commands) -> Vec<CommandError> {
+        commands.apply(self)
+    }
+
+    /// Store the commands to be applied during the next `update`
+    pub fn queue_commands(&mut self, mut commands: Commands) {
+        self.queued_commands.append(&mut commands);
+    }
+
+    /// Run the hook every time a component with the name is inserted onto an entity, either
+    /// added for the first time or replacing the component that was there. The hook is given
+    /// the entity and a buffer of commands, which are applied during the next `update`.
+    ///
+    /// ```
+    /// use bbecs::world::World;
+    ///
+    /// let mut world = World::new();
+    /// world.register("mesh").unwrap();
+    /// world.register("needs drawing").unwrap();
+    /// world.on_insert("mesh", |entity, commands| {
+    ///     commands.insert_component(entity, "needs drawing", true);
+    /// });
+    ///
+    /// let entity = world.spawn_entity().unwrap().with_component("mesh", 1_u32).unwrap().entity();
+    /// world.update().unwrap();
+    /// assert!(world.has_component(entity, "needs drawing").unwrap());
+    /// ```
+    pub fn on_insert<F: Fn(Entity, &mut Commands) + Storable>(&mut self, name: &str, hook: F) {
+        self.hooks.add(HookEvent::Insert, name, Box::new(hook));
+    }
+
+    /// Run the hook every time a component with the name is removed from an entity
+    pub fn on_remove<F: Fn(Entity, &mut Commands) + Storable>(&mut self, name: &str, hook: F) {
+        self.hooks.add(HookEvent::Remove, name, Box::new(hook));
+    }
+
+    /// Run the hook for every entity with a component of the name that is despawned, or
+    /// deleted by `update` after being flagged to be deleted. By the time the queued commands
+    /// are applied the entity is gone, so the hook should only use the entity as an id.
+    pub fn on_despawn<F: Fn(Entity, &mut Commands) + Storable>(&mut self, name: &str, hook: F) {
+        self.hooks.add(HookEvent::Despawn, name, Box::new(hook));
+    }
+
+    fn run_hooks(&mut self, event: HookEvent, name: &str, entity: Entity) {
+        self.hooks
+            .run(event, name, entity, &mut self.queued_commands);
+    }
+
+    /// Choose how `update` removes deleted entities from storage
+    pub fn set_deletion_strategy(&mut self, deletion_strategy: DeletionStrategy) {
+        self.deletion_strategy = deletion_strategy;
+    }
+
+    /// Remove the entity and all of its components from the world immediately, instead of
+    /// waiting for the next `update`. Any children of the entity are left without a parent.
+    ///
+    /// ```
+    /// use bbecs::world::World;
+    ///
+    /// let mut world = World::new();
+    /// let entity = world.spawn_entity().unwrap().entity();
+    ///
+    /// world.despawn(entity).unwrap();
+    /// assert!(!world.is_alive(entity));
+    /// ```
+    pub fn despawn(&mut self, entity: Entity) -> Result<()> {
+        let names = self.entity_data.component_names(entity)?;
+        self.hooks
+            .run_despawn(entity, names, &mut self.queued_commands);
+        self.entity_data.despawn(entity, self.deletion_strategy)?;
+        self.hierarchy.remove(entity);
         Ok(())
     }
 
-    pub fn delete_by_id(&self, id: u32) -> Result<()> {
-        let query_results = self.query(vec![TO_BE_DELETED, ENTITY_ID])?;
-        let query_to_be_deleted = query_results.get(TO_BE_DELETED).unwrap();
-        let query_ids = query_results.get(ENTITY_ID).unwrap();
+    /// Remove the entity along with all of its descendants from the world immediately
+    pub fn despawn_recursive(&mut self, entity: Entity) -> Result<()> {
+        let descendants = self.hierarchy.descendants(entity);
+        self.despawn(entity)?;
+        for descendant in descendants {
+            self.despawn(descendant)?;
+        }
+        Ok(())
+    }
 
-        for (index, component_id) in query_ids.iter().enumerate() {
-            let wrapped_component_id: &Rc<RefCell<u32>> = component_id.cast()?;
-            let component_id = wrapped_component_id.borrow();
+    /// Flag the entity to be deleted the next time `update` is called. Flagged entities are
+    /// no longer returned from queries. Any children of the entity are left without a parent
+    /// once it has been deleted. Flagging fails instead of panicking if the flag is already
+    /// borrowed.
+    pub fn delete_by_id(&self, entity: Entity) -> Result<()> {
+        let wrapped_to_be_deleted: &DataWrapper<bool> =
+            self.entity_data.get(entity, TO_BE_DELETED)?.cast()?;
+        let mut to_be_deleted = data_wrapper::try_borrow_mut(wrapped_to_be_deleted, TO_BE_DELETED)?;
+        *to_be_deleted = true;
+        Ok(())
+    }
+
+    /// Flag the entity along with all of its descendants to be deleted the next time `update`
+    /// is called
+    pub fn delete_recursive_by_id(&self, entity: Entity) -> Result<()> {
+        self.delete_by_id(entity)?;
+        for descendant in self.hierarchy.descendants(entity) {
+            self.delete_by_id(descendant)?;
+        }
+        Ok(())
+    }
 
-            if *component_id == id {
-                let wrapped_to_be_deleted: &Rc<RefCell<bool>> =
-                    query_to_be_deleted[index].cast()?;
-                let mut to_be_deleted = wrapped_to_be_deleted.borrow_mut();
-                *to_be_deleted = true;
-            }
+    /// Make the child entity a child of the parent, moving it away from any parent it already
+    /// had. An entity cannot become a child of itself or of one of its descendants.
+    ///
+    /// ```
+    /// use bbecs::world::World;
+    ///
+    /// let mut world = World::new();
+    /// let ship = world.spawn_entity().unwrap().entity();
+    /// let turret = world.spawn_entity().unwrap().entity();
+    /// let barrel = world.spawn_entity().unwrap().entity();
+    ///
+    /// world.set_parent(turret, ship).unwrap();
+    /// world.set_parent(barrel, turret).unwrap();
+    /// assert_eq!(world.children(ship), &[turret]);
+    /// assert_eq!(world.ancestors(barrel).collect::<Vec<_>>(), vec![turret, ship]);
+    /// assert!(world.set_parent(ship, barrel).is_err());
+    ///
+    /// world.despawn_recursive(ship).unwrap();
+    /// assert!(!world.is_alive(barrel));
+    /// ```
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) -> Result<()> {
+        self.check_alive(child)?;
+        self.check_alive(parent)?;
+        if child == parent || self.hierarchy.is_descendant(parent, child) {
+            return Err(BbEcsError::HierarchyCycle(child, parent));
+        }
+
+        self.hierarchy.set_parent(child, parent);
+        Ok(())
+    }
+
+    /// Take the entity away from its parent, returning the parent it had
+    pub fn remove_parent(&mut self, child: Entity) -> Result<Option<Entity>> {
+        self.check_alive(child)?;
+        Ok(self.hierarchy.remove_parent(child))
+    }
+
+    pub fn parent(&self, entity: Entity) -> Option<Entity> {
+        self.hierarchy.parent(entity)
+    }
+
+    /// The children of the entity, in the order they were given their parent
+    pub fn children(&self, entity: Entity) -> &[Entity] {
+        self.hierarchy.children(entity)
+    }
+
+    /// Iterate up the hierarchy from the parent of the entity to the root
+    pub fn ancestors(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        std::iter::successors(self.parent(entity), move |ancestor| self.parent(*ancestor))
+    }
+
+    /// Every entity below the entity in the hierarchy, with each parent before its children
+    pub fn descendants(&self, entity: Entity) -> Vec<Entity> {
+        self.hierarchy.descendants(entity)
+    }
+
+    /// Make the prefabs available to `spawn_prefab`, replacing any with the same name
+    #[cfg(feature = "serialize")]
+    pub fn add_prefabs(&mut self, prefabs: Prefabs) {
+        self.prefabs.extend(prefabs);
+    }
+
+    #[cfg(feature = "serialize")]
+    pub fn prefabs(&self) -> &Prefabs {
+        &self.prefabs
+    }
+
+    /// Spawn an entity from a prefab that has been added to the world, replacing the values
+    /// of some of its components with the overrides. Nothing is spawned if the prefab or the
+    /// overrides are not valid, and the error names the prefab and the field at fault. Every
+    /// component in the prefab needs to be registered first.
+    ///
+    /// ```
+    /// use bbecs::data_types::point::Point;
+    /// use bbecs::world::prefab::Prefabs;
+    /// use bbecs::world::World;
+    ///
+    /// let prefabs = Prefabs::from_json(
+    ///     r#"{ "asteroid": { "location": { "Point": { "x": 0.0, "y": 0.0 } }, "size": { "f32": 20.0 } } }"#,
+    /// )
+    /// .unwrap();
+    /// let mut world = World::new();
+    /// world.register("location").unwrap();
+    /// world.register("size").unwrap();
+    /// world.add_prefabs(prefabs);
+    ///
+    /// let asteroid = world
+    ///     .spawn_prefab("asteroid", &[("location", Point::new(5.0, 5.0).into())])
+    ///     .unwrap();
+    /// assert!(world.has_component(asteroid, "size").unwrap());
+    /// assert!(world.spawn_prefab("asteroid", &[("size", 3_u32.into())]).is_err());
+    /// ```
+    #[cfg(feature = "serialize")]
+    pub fn spawn_prefab(
+        &mut self,
+        name: &str,
+        overrides: &[(&str, PrefabValue)],
+    ) -> Result<Entity> {
+        prefab::spawn(self, name, overrides)
+    }
+
+    /// Work out the global transform of every entity with a local transform, by combining it
+    /// with the global transform of its parent. Entities in the hierarchy without a local
+    /// transform pass their parent's transform on to their children unchanged. Entities whose
+    /// local transform has been removed lose their global transform too. This is called
+    /// during `update`, but can also be called whenever the global transforms are needed
+    /// straight away.
+    ///
+    /// ```
+    /// use bbecs::data_types::point::Point;
+    /// use bbecs::data_types::transform::Transform;
+    /// use bbecs::world::{World, GLOBAL_TRANSFORM, LOCAL_TRANSFORM};
+    ///
+    /// let mut world = World::new();
+    /// let ship = world
+    ///     .spawn_entity()
+    ///     .unwrap()
+    ///     .with_component(LOCAL_TRANSFORM, Transform::new(Point::new(10.0, 5.0)))
+    ///     .unwrap()
+    ///     .entity();
+    /// world
+    ///     .spawn_entity()
+    ///     .unwrap()
+    ///     .with_component(LOCAL_TRANSFORM, Transform::new(Point::new(1.0, 0.0)))
+    ///     .unwrap()
+    ///     .with_parent(ship)
+    ///     .unwrap();
+    ///
+    /// world.propagate_transforms().unwrap();
+    /// let mut positions: Vec<Point> = world
+    ///     .query_typed::<(&Transform,)>([GLOBAL_TRANSFORM])
+    ///     .unwrap()
+    ///     .map(|(transform,)| transform.position)
+    ///     .collect();
+    /// positions.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+    /// assert_eq!(positions, vec![Point::new(10.0, 5.0), Point::new(11.0, 5.0)]);
+    /// ```
+    pub fn propagate_transforms(&mut self) -> Result<()> {
+        let locals: HashMap<Entity, Transform> = self
+            .query_typed::<(&Entity, &Transform)>([ENTITY_ID, LOCAL_TRANSFORM])?
+            .map(|(entity, local)| (*entity, *local))
+            .collect();
+
+        let mut globals = HashMap::new();
+        for entity in locals.keys() {
+            self.global_transform(*entity, &locals, &mut globals);
+        }
+
+        for (entity, _) in locals {
+            self.set_global_transform(entity, globals[&entity])?;
+        }
+
+        let filter = QueryFilter::new()
+            .with(GLOBAL_TRANSFORM)
+            .without(LOCAL_TRANSFORM);
+        let stale: Vec<Entity> = self
+            .query_typed_filtered::<(&Entity,)>([ENTITY_ID], &filter)?
+            .map(|(entity,)| *entity)
+            .collect();
+        for entity in stale {
+            self.remove_component::<Transform>(entity, GLOBAL_TRANSFORM)?;
+        }
+        Ok(())
+    }
+
+    fn global_transform(
+        &self,
+        entity: Entity,
+        locals: &HashMap<Entity, Transform>,
+        globals: &mut HashMap<Entity, Transform>,
+    ) -> Transform {
+        if let Some(global) = globals.get(&entity) {
+            return *global;
+        }
+
+        let local = locals.get(&entity).copied().unwrap_or_default();
+        let global = match self.parent(entity) {
+            Some(parent) => self
+                .global_transform(parent, locals, globals)
+                .mul_transform(&local),
+            None => local,
+        };
+        globals.insert(entity, global);
+        global
+    }
+
+    /// Only global transforms that are different are written, so that they are not marked as
+    /// changed every update
+    fn set_global_transform(&mut self, entity: Entity, global: Transform) -> Result<()> {
+        if !self.has_component(entity, GLOBAL_TRANSFORM)? {
+            return self.insert_component(entity, GLOBAL_TRANSFORM, global);
+        }
+
+        let component = self.entity_data.get(entity, GLOBAL_TRANSFORM)?;
+        let wrapper: &DataWrapper<Transform> = component.cast()?;
+        if *wrapper.borrow() != global {
+            *wrapper.borrow_mut() = global;
+            component.ticks().set_changed(self.change_tick);
+        }
+        Ok(())
+    }
+
+    fn check_alive(&self, entity: Entity) -> Result<()> {
+        if !self.is_alive(entity) {
+            return Err(BbEcsError::EntityNotAlive(entity));
         }
         Ok(())
     }
@@ -136,31 +795,34 @@ impl World {
 impl Default for World {
     fn default() -> Self {
         let mut entity_data = EntityData::new();
-        let mut bitmap = BitMap::new();
 
         entity_data.register(TO_BE_DELETED.into()).unwrap();
-        bitmap.register(TO_BE_DELETED.into());
         entity_data.register(ENTITY_ID.into()).unwrap();
-        bitmap.register(ENTITY_ID.into());
+        entity_data
+            .register_typed::<Transform>(LOCAL_TRANSFORM.into())
+            .unwrap();
+        entity_data
+            .register_typed::<Transform>(GLOBAL_TRANSFORM.into())
+            .unwrap();
 
         Self {
             entity_data,
             resources: ResourcesData::new(),
             is_empty: true,
-            next_entity_id: 0,
-            bitmap,
+            deletion_strategy: DeletionStrategy::default(),
+            queued_commands: Commands::new(),
+            events_updaters: HashMap::new(),
+            change_tick: 1,
+            hierarchy: Hierarchy::default(),
+            hooks: Hooks::default(),
+            #[cfg(feature = "serialize")]
+            prefabs: Prefabs::default(),
         }
     }
 }
 
-impl_world_trait!(Color, Color);
-impl_world_trait!(Mesh, Mesh);
-impl_world_trait!(Point, Point);
-impl_world_trait!(u32, U32);
-impl_world_trait!(f32, F32);
-impl_world_trait!(usize, Usize);
-impl_world_trait!(bool, Bool);
-impl_world_trait!(KeyCode, GgezKeyCode);
-impl_world_trait!(String, Marker);
-impl_world_trait!(Text, GgezText);
-impl_world_trait!(SoundData, GgezSound);
+impl<T: Storable> WorldMethods<T> for World {
+    fn add_resource(&mut self, name: String, data: T) {
+        self.resources.insert(name, Resource::new(data));
+    }
+}
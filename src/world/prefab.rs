@@ -0,0 +1,255 @@
+//! Named templates for entities, loaded from JSON, RON or TOML. Each prefab lists the
+//! components an entity spawned from it will have, along with their values, so spawning an
+//! enemy does not need a long chain of `with_component` calls.
+//!
+//! Every component value names its kind, which decides the type that is stored in the world.
+//! In RON an asteroid could look like
+//!
+//! ```ron
+//! {
+//!     "asteroid": {
+//!         "location": Point((x: 100.0, y: 50.0)),
+//!         "size": f32(20.0),
+//!         "points": u32(10),
+//!         "asteroid": Marker("asteroid"),
+//!     },
+//! }
+//! ```
+
+use std::any::TypeId;
+use std::collections::BTreeMap;
+
+use ggez::graphics::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::components::ComponentData;
+use crate::data_types::point::Point;
+use crate::errors::{BbEcsError, Result};
+
+use super::{Entity, World, ENTITY_ID, TO_BE_DELETED};
+
+/// The value of a single component in a prefab. Markers are stored in the world as a
+/// `String`, and colors as a ggez `Color`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PrefabValue {
+    Point(Point),
+    #[serde(rename = "f32")]
+    F32(f32),
+    #[serde(rename = "u32")]
+    U32(u32),
+    #[serde(rename = "bool")]
+    Bool(bool),
+    Color([f32; 4]),
+    Marker(String),
+}
+
+impl PrefabValue {
+    /// The name of the kind of value, as it is written in a prefab file
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PrefabValue::Point(_) => "Point",
+            PrefabValue::F32(_) => "f32",
+            PrefabValue::U32(_) => "u32",
+            PrefabValue::Bool(_) => "bool",
+            PrefabValue::Color(_) => "Color",
+            PrefabValue::Marker(_) => "Marker",
+        }
+    }
+
+    fn type_id(&self) -> TypeId {
+        match self {
+            PrefabValue::Point(_) => TypeId::of::<Point>(),
+            PrefabValue::F32(_) => TypeId::of::<f32>(),
+            PrefabValue::U32(_) => TypeId::of::<u32>(),
+            PrefabValue::Bool(_) => TypeId::of::<bool>(),
+            PrefabValue::Color(_) => TypeId::of::<Color>(),
+            PrefabValue::Marker(_) => TypeId::of::<String>(),
+        }
+    }
+
+    fn into_component(self) -> ComponentData {
+        match self {
+            PrefabValue::Point(point) => ComponentData::new(point),
+            PrefabValue::F32(number) => ComponentData::new(number),
+            PrefabValue::U32(number) => ComponentData::new(number),
+            PrefabValue::Bool(flag) => ComponentData::new(flag),
+            PrefabValue::Color(color) => ComponentData::new(Color::from(color)),
+            PrefabValue::Marker(marker) => ComponentData::new(marker),
+        }
+    }
+}
+
+impl From<Point> for PrefabValue {
+    fn from(point: Point) -> Self {
+        PrefabValue::Point(point)
+    }
+}
+
+impl From<f32> for PrefabValue {
+    fn from(number: f32) -> Self {
+        PrefabValue::F32(number)
+    }
+}
+
+impl From<u32> for PrefabValue {
+    fn from(number: u32) -> Self {
+        PrefabValue::U32(number)
+    }
+}
+
+impl From<bool> for PrefabValue {
+    fn from(flag: bool) -> Self {
+        PrefabValue::Bool(flag)
+    }
+}
+
+impl From<Color> for PrefabValue {
+    fn from(color: Color) -> Self {
+        PrefabValue::Color(color.into())
+    }
+}
+
+/// The components of a prefab by name
+pub type Prefab = BTreeMap<String, PrefabValue>;
+
+/// A collection of prefabs by name, usually loaded from a data file
+///
+/// ```
+/// use bbecs::world::prefab::Prefabs;
+///
+/// let prefabs = Prefabs::from_toml(
+///     r#"
+///     [asteroid]
+///     location = { Point = { x = 100.0, y = 50.0 } }
+///     size = { f32 = 20.0 }
+///     "#,
+/// )
+/// .unwrap();
+/// assert_eq!(prefabs.get("asteroid").unwrap().len(), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Prefabs {
+    prefabs: BTreeMap<String, Prefab>,
+}
+
+impl Prefabs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_json(data: &str) -> Result<Self> {
+        Ok(serde_json::from_str(data)?)
+    }
+
+    pub fn from_ron(data: &str) -> Result<Self> {
+        Ok(ron::from_str(data)?)
+    }
+
+    pub fn from_toml(data: &str) -> Result<Self> {
+        Ok(toml::from_str(data)?)
+    }
+
+    /// Add a prefab, replacing any prefab that had the same name
+    pub fn insert<S: ToString>(&mut self, name: S, prefab: Prefab) {
+        self.prefabs.insert(name.to_string(), prefab);
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Prefab> {
+        self.prefabs
+            .get(name)
+            .ok_or_else(|| BbEcsError::PrefabNotFound(name.to_owned()))
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.prefabs.keys()
+    }
+
+    /// Add every prefab from the other collection, replacing any with the same name
+    pub fn extend(&mut self, other: Prefabs) {
+        self.prefabs.extend(other.prefabs);
+    }
+}
+
+/// Spawn an entity from one of the world's prefabs, with the overrides replacing the values
+/// of some of its components. Everything is checked before the entity is spawned, so nothing
+/// is added to the world if the prefab or the overrides are not valid. The entity is spawned
+/// with all of its components at once, as a bundle.
+pub(crate) fn spawn(
+    world: &mut World,
+    name: &str,
+    overrides: &[(&str, PrefabValue)],
+) -> Result<Entity> {
+    let mut prefab = world.prefabs.get(name)?.clone();
+
+    for (field, value) in overrides {
+        let current = prefab.get_mut(*field).ok_or_else(|| {
+            invalid(
+                name,
+                field,
+                "is not in the prefab so it cannot be overridden",
+            )
+        })?;
+        if current.kind() != value.kind() {
+            let reason = format!(
+                "is a `{}` but was overridden with a `{}`",
+                current.kind(),
+                value.kind()
+            );
+            return Err(invalid(name, field, &reason));
+        }
+        *current = value.clone();
+    }
+
+    for (field, value) in &prefab {
+        validate(world, name, field, value)?;
+    }
+
+    let components: Vec<(String, ComponentData)> = prefab
+        .into_iter()
+        .map(|(field, value)| (field, value.into_component()))
+        .collect();
+    Ok(world.spawn_bundle(components)?.entity())
+}
+
+fn validate(world: &World, prefab: &str, field: &str, value: &PrefabValue) -> Result<()> {
+    if field == ENTITY_ID || field == TO_BE_DELETED {
+        return Err(invalid(
+            prefab,
+            field,
+            "is managed by the world and cannot be set",
+        ));
+    }
+    if !world.is_registered(field) {
+        return Err(invalid(prefab, field, "is not a registered component"));
+    }
+
+    if let Some(type_id) = world.entity_data.registered_type(field) {
+        if type_id != value.type_id() {
+            let reason = format!(
+                "is a `{}` but the component stores a different type",
+                value.kind()
+            );
+            return Err(invalid(prefab, field, &reason));
+        }
+    }
+
+    if let PrefabValue::Color(channels) = value {
+        if channels
+            .iter()
+            .any(|channel| !(0.0..=1.0).contains(channel))
+        {
+            return Err(invalid(
+                prefab,
+                field,
+                "has a color channel outside of 0 to 1",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn invalid(prefab: &str, field: &str, reason: &str) -> BbEcsError {
+    BbEcsError::InvalidPrefab(prefab.to_owned(), field.to_owned(), reason.to_owned())
+}
@@ -0,0 +1,528 @@
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+
+use crate::components::{CastComponents, ComponentData, ComponentTicks};
+use crate::errors::{BbEcsError, Result};
+
+use super::archetype::Archetype;
+use super::data_wrapper::{self, DataRef, DataRefMut, DataWrapper, Storable};
+
+/// Narrows down the entities that a query returns. Added and changed filters compare the
+/// ticks of a component against the `since` tick, which is usually the world's change tick
+/// from the last time the system ran. Entities must have every component that an added,
+/// changed or with filter mentions, and none of the components in a without filter.
+///
+/// ```
+/// use bbecs::data_types::point::Point;
+/// use bbecs::world::query::QueryFilter;
+/// use bbecs::world::World;
+///
+/// let mut world = World::new();
+/// world.register("location").unwrap();
+/// let still = world.spawn_entity().unwrap().entity();
+/// world.insert_component(still, "location", Point::new(0.0, 0.0)).unwrap();
+/// let moving = world.spawn_entity().unwrap().entity();
+/// world.insert_component(moving, "location", Point::new(0.0, 0.0)).unwrap();
+///
+/// let last_run = world.change_tick();
+/// world.update().unwrap();
+/// world.insert_component(moving, "location", Point::new(5.0, 0.0)).unwrap();
+///
+/// let filter = QueryFilter::new().changed("location").since(last_run);
+/// let moved: Vec<_> = world
+///     .query_typed_filtered::<(&Point,)>(["location"], &filter)
+///     .unwrap()
+///     .map(|(location,)| *location)
+///     .collect();
+/// assert_eq!(moved, vec![Point::new(5.0, 0.0)]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct QueryFilter {
+    with: Vec<String>,
+    without: Vec<String>,
+    optional: Vec<String>,
+    added: Vec<String>,
+    changed: Vec<String>,
+    since: u32,
+}
+
+impl QueryFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only return entities that have the component, without fetching it
+    pub fn with(mut self, name: &str) -> Self {
+        self.with.push(name.to_owned());
+        self
+    }
+
+    /// Only return entities that do not have the component
+    pub fn without(mut self, name: &str) -> Self {
+        self.without.push(name.to_owned());
+        self
+    }
+
+    /// Fetch the component for the entities that have it, without leaving out the entities
+    /// that do not. Optional components are read from the results with `get_optional`.
+    pub fn optional(mut self, name: &str) -> Self {
+        self.optional.push(name.to_owned());
+        self
+    }
+
+    /// Only return entities where the component was added after the `since` tick
+    pub fn added(mut self, name: &str) -> Self {
+        self.added.push(name.to_owned());
+        self
+    }
+
+    /// Only return entities where the component was added or changed after the `since` tick
+    pub fn changed(mut self, name: &str) -> Self {
+        self.changed.push(name.to_owned());
+        self
+    }
+
+    /// Set the tick that added and changed components are compared against
+    pub fn since(mut self, tick: u32) -> Self {
+        self.since = tick;
+        self
+    }
+
+    pub(crate) fn optional_names(&self) -> &[String] {
+        &self.optional
+    }
+
+    /// Every component name that the filter mentions
+    pub(crate) fn names(&self) -> impl Iterator<Item = &str> {
+        self.required_names()
+            .chain(self.without.iter())
+            .chain(self.optional.iter())
+            .map(String::as_str)
+    }
+
+    fn required_names(&self) -> impl Iterator<Item = &String> {
+        self.with
+            .iter()
+            .chain(self.added.iter())
+            .chain(self.changed.iter())
+    }
+
+    pub(crate) fn matches_archetype(&self, archetype: &Archetype) -> bool {
+        self.required_names()
+            .all(|name| archetype.has_component(name))
+            && !self
+                .without
+                .iter()
+                .any(|name| archetype.has_component(name))
+    }
+
+    pub(crate) fn matches_row(&self, archetype: &Archetype, row: usize) -> bool {
+        let ticks = |name: &String| archetype.column(name).map(|column| column[row].ticks());
+
+        self.added
+            .iter()
+            .all(|name| ticks(name).is_some_and(|ticks| ticks.is_added_since(self.since)))
+            && self
+                .changed
+                .iter()
+                .all(|name| ticks(name).is_some_and(|ticks| ticks.is_changed_since(self.since)))
+    }
+}
+
+/// The components found by `World::query_filtered`. Every name has a list with an entry for
+/// each entity, and the entities are in the same order in every list.
+#[derive(Debug)]
+pub struct QueryResults<'w> {
+    components: HashMap<String, Vec<&'w ComponentData>>,
+    optional_components: HashMap<String, Vec<Option<&'w ComponentData>>>,
+    len: usize,
+}
+
+impl<'w> QueryResults<'w> {
+    pub(crate) fn new(names: &[&str], optional_names: &[String]) -> Self {
+        Self {
+            components: names
+                .iter()
+                .map(|name| ((*name).to_owned(), vec![]))
+                .collect(),
+            optional_components: optional_names
+                .iter()
+                .map(|name| (name.clone(), vec![]))
+                .collect(),
+            len: 0,
+        }
+    }
+
+    /// Add the components from the rows of an archetype that has all of the required
+    /// components
+    pub(crate) fn extend(&mut self, archetype: &'w Archetype, rows: &[usize]) {
+        for (name, components) in self.components.iter_mut() {
+            if let Some(column) = archetype.column(name) {
+                components.extend(rows.iter().map(|row| &column[*row]));
+            }
+        }
+
+        for (name, components) in self.optional_components.iter_mut() {
+            let column = archetype.column(name);
+            components.extend(rows.iter().map(|row| column.map(|column| &column[*row])));
+        }
+
+        self.len += rows.len();
+    }
+
+    /// The components for a name that was required by the query
+    pub fn get(&self, name: &str) -> Option<&Vec<&'w ComponentData>> {
+        self.components.get(name)
+    }
+
+    /// The components for a name that was optional in the query, with `None` for the
+    /// entities that do not have the component
+    pub fn get_optional(&self, name: &str) -> Option<&Vec<Option<&'w ComponentData>>> {
+        self.optional_components.get(name)
+    }
+
+    /// The number of entities that matched the query
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub(crate) fn into_components(self) -> HashMap<String, Vec<&'w ComponentData>> {
+        self.components
+    }
+}
+
+/// A single component type that can be requested in a typed query. Implemented for `&T`,
+/// which borrows the component immutably, `&mut T`, which borrows it mutably, and `Option`s
+/// of both for components that not every entity has.
+pub trait Fetch<'w> {
+    type Item;
+    type Column;
+
+    /// Whether entities without the component are still returned by the query
+    fn is_optional() -> bool {
+        false
+    }
+
+    fn column(results: &QueryResults<'w>, name: &str, change_tick: u32) -> Result<Self::Column>;
+    fn fetch(column: &Self::Column, index: usize) -> Self::Item;
+    /// Fetch the component, failing instead of panicking if it is already borrowed in a way
+    /// that conflicts
+    fn try_fetch(column: &Self::Column, name: &str, index: usize) -> Result<Self::Item>;
+}
+
+impl<'w, T: Storable> Fetch<'w> for &T {
+    type Item = DataRef<'w, T>;
+    type Column = Vec<&'w DataWrapper<T>>;
+
+    fn column(results: &QueryResults<'w>, name: &str, _change_tick: u32) -> Result<Self::Column> {
+        required_column(results, name)?
+            .iter()
+            .map(|component| component.cast())
+            .collect()
+    }
+
+    fn fetch(column: &Self::Column, index: usize) -> Self::Item {
+        column[index].borrow()
+    }
+
+    fn try_fetch(column: &Self::Column, name: &str, index: usize) -> Result<Self::Item> {
+        data_wrapper::try_borrow(column[index], name)
+    }
+}
+
+impl<'w, T: Storable> Fetch<'w> for &mut T {
+    type Item = Mut<'w, T>;
+    type Column = (Vec<(&'w DataWrapper<T>, &'w ComponentTicks)>, u32);
+
+    fn column(results: &QueryResults<'w>, name: &str, change_tick: u32) -> Result<Self::Column> {
+        let column = required_column(results, name)?
+            .iter()
+            .map(|component| Ok((component.cast()?, component.ticks())))
+            .collect::<Result<_>>()?;
+        Ok((column, change_tick))
+    }
+
+    fn fetch((column, change_tick): &Self::Column, index: usize) -> Self::Item {
+        let (wrapper, ticks) = column[index];
+        Mut::new(wrapper, ticks, *change_tick)
+    }
+
+    fn try_fetch(
+        (column, change_tick): &Self::Column,
+        name: &str,
+        index: usize,
+    ) -> Result<Self::Item> {
+        let (wrapper, ticks) = column[index];
+        Mut::try_new(wrapper, name, ticks, *change_tick)
+    }
+}
+
+impl<'w, T: Storable> Fetch<'w> for Option<&T> {
+    type Item = Option<DataRef<'w, T>>;
+    type Column = Vec<Option<&'w DataWrapper<T>>>;
+
+    fn is_optional() -> bool {
+        true
+    }
+
+    fn column(results: &QueryResults<'w>, name: &str, _change_tick: u32) -> Result<Self::Column> {
+        optional_column(results, name)?
+            .iter()
+            .map(|component| component.map(|component| component.cast()).transpose())
+            .collect()
+    }
+
+    fn fetch(column: &Self::Column, index: usize) -> Self::Item {
+        column[index].map(|wrapper| wrapper.borrow())
+    }
+
+    fn try_fetch(column: &Self::Column, name: &str, index: usize) -> Result<Self::Item> {
+        column[index]
+            .map(|wrapper| data_wrapper::try_borrow(wrapper, name))
+            .transpose()
+    }
+}
+
+impl<'w, T: Storable> Fetch<'w> for Option<&mut T> {
+    type Item = Option<Mut<'w, T>>;
+    type Column = (Vec<Option<(&'w DataWrapper<T>, &'w ComponentTicks)>>, u32);
+
+    fn is_optional() -> bool {
+        true
+    }
+
+    fn column(results: &QueryResults<'w>, name: &str, change_tick: u32) -> Result<Self::Column> {
+        let column = optional_column(results, name)?
+            .iter()
+            .map(|component| {
+                component
+                    .map(|component| Ok((component.cast()?, component.ticks())))
+                    .transpose()
+            })
+            .collect::<Result<_>>()?;
+        Ok((column, change_tick))
+    }
+
+    fn fetch((column, change_tick): &Self::Column, index: usize) -> Self::Item {
+        column[index].map(|(wrapper, ticks)| Mut::new(wrapper, ticks, *change_tick))
+    }
+
+    fn try_fetch(
+        (column, change_tick): &Self::Column,
+        name: &str,
+        index: usize,
+    ) -> Result<Self::Item> {
+        column[index]
+            .map(|(wrapper, ticks)| Mut::try_new(wrapper, name, ticks, *change_tick))
+            .transpose()
+    }
+}
+
+/// A component borrowed mutably from a typed query. The component is marked as changed
+/// when it is mutably dereferenced, so only reading it does not trigger changed filters.
+pub struct Mut<'w, T> {
+    data: DataRefMut<'w, T>,
+    ticks: &'w ComponentTicks,
+    change_tick: u32,
+}
+
+impl<'w, T> Mut<'w, T> {
+    fn new(wrapper: &'w DataWrapper<T>, ticks: &'w ComponentTicks, change_tick: u32) -> Self {
+        Self {
+            data: wrapper.borrow_mut(),
+            ticks,
+            change_tick,
+        }
+    }
+
+    pub(crate) fn try_new(
+        wrapper: &'w DataWrapper<T>,
+        name: &str,
+        ticks: &'w ComponentTicks,
+        change_tick: u32,
+    ) -> Result<Self> {
+        Ok(Self {
+            data: data_wrapper::try_borrow_mut(wrapper, name)?,
+            ticks,
+            change_tick,
+        })
+    }
+}
+
+impl<'w, T> Deref for Mut<'w, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.data
+    }
+}
+
+impl<'w, T> DerefMut for Mut<'w, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.ticks.set_changed(self.change_tick);
+        &mut self.data
+    }
+}
+
+/// A tuple of `Fetch` types that make up a typed query, for example `(&Point, &mut f32)`.
+/// Each element of the tuple is matched up with the component name in the same position.
+pub trait QueryData<'w> {
+    type Item;
+    type Columns;
+
+    /// The names in the positions of optional elements of the tuple
+    fn optional_names<'n>(names: &[&'n str]) -> Vec<&'n str>;
+    fn columns(
+        results: &QueryResults<'w>,
+        names: &[&str],
+        change_tick: u32,
+    ) -> Result<Self::Columns>;
+    fn fetch(columns: &Self::Columns, index: usize) -> Self::Item;
+    fn try_fetch(columns: &Self::Columns, names: &[String], index: usize) -> Result<Self::Item>;
+}
+
+macro_rules! impl_query_data {
+    ($count:expr, $($fetch:ident: $index:tt),+) => {
+        impl<'w, $($fetch: Fetch<'w>),+> QueryData<'w> for ($($fetch,)+) {
+            type Item = ($($fetch::Item,)+);
+            type Columns = ($($fetch::Column,)+);
+
+            fn optional_names<'n>(names: &[&'n str]) -> Vec<&'n str> {
+                let mut optional_names = vec![];
+                $(
+                    if $fetch::is_optional() {
+                        if let Some(name) = names.get($index) {
+                            optional_names.push(*name);
+                        }
+                    }
+                )+
+                optional_names
+            }
+
+            fn columns(
+                results: &QueryResults<'w>,
+                names: &[&str],
+                change_tick: u32,
+            ) -> Result<Self::Columns> {
+                if names.len() != $count {
+                    return Err(BbEcsError::QueryNameCountMismatch($count, names.len()));
+                }
+                for (index, name) in names.iter().enumerate() {
+                    if names[..index].contains(name) {
+                        return Err(BbEcsError::DuplicateQueryName((*name).to_owned()));
+                    }
+                }
+
+                Ok(($($fetch::column(results, names[$index], change_tick)?,)+))
+            }
+
+            fn fetch(columns: &Self::Columns, index: usize) -> Self::Item {
+                ($($fetch::fetch(&columns.$index, index),)+)
+            }
+
+            fn try_fetch(
+                columns: &Self::Columns,
+                names: &[String],
+                index: usize,
+            ) -> Result<Self::Item> {
+                Ok(($($fetch::try_fetch(&columns.$index, &names[$index], index)?,)+))
+            }
+        }
+    };
+}
+
+impl_query_data!(1, A: 0);
+impl_query_data!(2, A: 0, B: 1);
+impl_query_data!(3, A: 0, B: 1, C: 2);
+impl_query_data!(4, A: 0, B: 1, C: 2, D: 3);
+impl_query_data!(5, A: 0, B: 1, C: 2, D: 3, E: 4);
+impl_query_data!(6, A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+impl_query_data!(7, A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+impl_query_data!(8, A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+
+fn required_column<'r, 'w>(
+    results: &'r QueryResults<'w>,
+    name: &str,
+) -> Result<&'r [&'w ComponentData]> {
+    if let Some(components) = results.get(name) {
+        Ok(components)
+    } else {
+        Err(BbEcsError::ComponentNotFound(name.to_owned()))
+    }
+}
+
+fn optional_column<'r, 'w>(
+    results: &'r QueryResults<'w>,
+    name: &str,
+) -> Result<&'r [Option<&'w ComponentData>]> {
+    if let Some(components) = results.get_optional(name) {
+        Ok(components)
+    } else {
+        Err(BbEcsError::ComponentNotFound(name.to_owned()))
+    }
+}
+
+/// Iterator over the results of `World::query_typed`. Every item is a tuple with the
+/// components of one entity already borrowed.
+pub struct TypedQuery<'w, Q: QueryData<'w>> {
+    columns: Q::Columns,
+    names: Vec<String>,
+    index: usize,
+    length: usize,
+}
+
+impl<'w, Q: QueryData<'w>> TypedQuery<'w, Q> {
+    pub(crate) fn new(
+        results: &QueryResults<'w>,
+        names: &[&str],
+        change_tick: u32,
+    ) -> Result<Self> {
+        let columns = Q::columns(results, names, change_tick)?;
+
+        Ok(Self {
+            columns,
+            names: names.iter().map(|name| (*name).to_owned()).collect(),
+            index: 0,
+            length: results.len(),
+        })
+    }
+
+    /// Move on to the next entity like `next`, but return an error naming the component
+    /// instead of panicking when one of its components is already borrowed in a way that
+    /// conflicts. The entity is skipped either way, so the rest of the query can still be
+    /// iterated after an error.
+    pub fn try_next(&mut self) -> Option<Result<Q::Item>> {
+        if self.index >= self.length {
+            return None;
+        }
+
+        let item = Q::try_fetch(&self.columns, &self.names, self.index);
+        self.index += 1;
+        Some(item)
+    }
+}
+
+impl<'w, Q: QueryData<'w>> Iterator for TypedQuery<'w, Q> {
+    type Item = Q::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.length {
+            return None;
+        }
+
+        let item = Q::fetch(&self.columns, self.index);
+        self.index += 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.length - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'w, Q: QueryData<'w>> ExactSizeIterator for TypedQuery<'w, Q> {}
@@ -0,0 +1,464 @@
+//! Saving a world to JSON or RON and loading it back again. Components and resources are
+//! stored with a key for their type, so only types that have been registered with the
+//! `WorldSerializer` can be saved. Anything else, such as ggez meshes or sounds, is skipped
+//! unless a hook that knows how to save and rebuild it has been registered with
+//! `WorldSerializer::register_with`.
+
+use std::any::TypeId;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::rc::Rc;
+
+use ggez::graphics::Color;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+pub use serde_json::Value;
+
+use crate::components::{CastComponents, ComponentData};
+use crate::data_types::point::Point;
+use crate::data_types::transform::Transform;
+use crate::errors::{BbEcsError, Result};
+use crate::resources::resource::{Resource, ResourceCast};
+
+use super::{DataWrapper, Entity, Storable, World, WorldMethods, ENTITY_ID, TO_BE_DELETED};
+
+/// Everything in a world that could be saved. This is what gets written out as JSON or RON.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedWorld {
+    pub components: Vec<String>,
+    pub entities: Vec<SavedEntity>,
+    pub resources: BTreeMap<String, SavedValue>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedEntity {
+    pub id: Entity,
+    pub parent: Option<Entity>,
+    pub components: BTreeMap<String, SavedValue>,
+}
+
+/// A single component or resource along with the key of its type
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedValue {
+    #[serde(rename = "type")]
+    pub type_key: String,
+    pub value: Value,
+}
+
+/// Maps the entities in a save to the entities that were spawned when loading it
+#[derive(Debug, Default)]
+pub struct EntityMap {
+    entities: HashMap<Entity, Entity>,
+}
+
+impl EntityMap {
+    /// The loaded entity for an entity from the save
+    pub fn get(&self, saved: Entity) -> Result<Entity> {
+        if let Some(entity) = self.entities.get(&saved) {
+            Ok(*entity)
+        } else {
+            Err(BbEcsError::EntityNotSaved(saved))
+        }
+    }
+
+    /// The loaded entity for an entity from the save, or `Entity::DANGLING` if it was not
+    /// saved, such as when a component still refers to an entity that has been deleted
+    pub fn get_or_dangling(&self, saved: Entity) -> Entity {
+        self.entities
+            .get(&saved)
+            .copied()
+            .unwrap_or(Entity::DANGLING)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+enum LoadTarget<'a> {
+    Component(Entity, &'a str),
+    Resource(&'a str),
+}
+
+type SaveComponentFn = Box<dyn Fn(&ComponentData) -> Result<Value>>;
+type SaveResourceFn = Box<dyn Fn(&Resource) -> Result<Value>>;
+/// Puts a value that has already been loaded into the world
+type InsertFn = Box<dyn FnOnce(&mut World, LoadTarget<'_>) -> Result<()>>;
+type LoadFn = Box<dyn Fn(Value, &EntityMap) -> Result<InsertFn>>;
+
+#[derive(Default)]
+struct LoadedValues {
+    components: Vec<(Entity, String, InsertFn)>,
+    resources: Vec<(String, InsertFn)>,
+}
+
+struct TypeHandler {
+    key: String,
+    type_id: TypeId,
+    type_name: &'static str,
+    save_component: SaveComponentFn,
+    save_resource: SaveResourceFn,
+    load: LoadFn,
+}
+
+/// Saves worlds and loads them back, knowing how to save each registered type. The types
+/// that bbecs provides, along with the primitive types and `String`, are registered already.
+///
+/// ```
+/// use bbecs::data_types::point::Point;
+/// use bbecs::world::serialize::WorldSerializer;
+/// use bbecs::world::World;
+///
+/// let mut world = World::new();
+/// world.register("location").unwrap();
+/// let ship = world
+///     .spawn_entity()
+///     .unwrap()
+///     .with_component("location", Point::new(5.0, 2.0))
+///     .unwrap()
+///     .entity();
+/// world.spawn_entity().unwrap().with_parent(ship).unwrap();
+///
+/// let serializer = WorldSerializer::new();
+/// let saved = serializer.to_ron(&world).unwrap();
+///
+/// let mut loaded = World::new();
+/// let entities = serializer.from_ron(&mut loaded, &saved).unwrap();
+/// let loaded_ship = entities.get(ship).unwrap();
+/// assert_eq!(loaded.children(loaded_ship).len(), 1);
+/// ```
+pub struct WorldSerializer {
+    handlers: HashMap<TypeId, TypeHandler>,
+    type_ids: HashMap<String, TypeId>,
+}
+
+impl WorldSerializer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow components and resources of this type to be saved. The key is written into the
+    /// save to find the type again when loading, so it should not change between versions.
+    pub fn register<T: Serialize + DeserializeOwned + Storable>(&mut self, key: &str) -> &mut Self {
+        self.register_with(
+            key,
+            |data: &T| Ok(serde_json::to_value(data)?),
+            |value, _| Ok(serde_json::from_value(value)?),
+        )
+    }
+
+    /// Register hooks to save and load a type that cannot be serialized directly, or that
+    /// holds entities which need to be remapped when loading. The load hook is given the map
+    /// from saved entities to the entities in the loaded world.
+    ///
+    /// ```
+    /// use bbecs::world::serialize::WorldSerializer;
+    /// use bbecs::world::World;
+    ///
+    /// struct Sound {
+    ///     path: String,
+    /// }
+    ///
+    /// let mut serializer = WorldSerializer::new();
+    /// serializer.register_with(
+    ///     "Sound",
+    ///     |sound: &Sound| Ok(sound.path.clone().into()),
+    ///     |value, _| {
+    ///         let path = serde_json::from_value(value)?;
+    ///         Ok(Sound { path })
+    ///     },
+    /// );
+    /// ```
+    pub fn register_with<T, S, L>(&mut self, key: &str, save: S, load: L) -> &mut Self
+    where
+        T: Storable,
+        S: Fn(&T) -> Result<Value> + 'static,
+        L: Fn(Value, &EntityMap) -> Result<T> + 'static,
+    {
+        let save_component = Rc::new(save);
+        let save_resource = save_component.clone();
+
+        let handler = TypeHandler {
+            key: key.to_owned(),
+            type_id: TypeId::of::<T>(),
+            type_name: std::any::type_name::<T>(),
+            save_component: Box::new(move |component| {
+                let wrapper: &DataWrapper<T> = component.cast()?;
+                let data = wrapper.borrow();
+                save_component(&data)
+            }),
+            save_resource: Box::new(move |resource| save_resource(resource.cast()?)),
+            load: Box::new(move |value, entities| {
+                let data = load(value, entities)?;
+                Ok(Box::new(move |world: &mut World, target| match target {
+                    LoadTarget::Component(entity, name) => {
+                        world.insert_component(entity, name, data)
+                    }
+                    LoadTarget::Resource(name) => {
+                        world.add_resource(name.to_owned(), data);
+                        Ok(())
+                    }
+                }))
+            }),
+        };
+
+        self.type_ids.insert(key.to_owned(), TypeId::of::<T>());
+        self.handlers.insert(TypeId::of::<T>(), handler);
+        self
+    }
+
+    /// Take everything out of the world that can be saved. Entities flagged to be deleted are
+    /// left out, along with any components and resources with types that are not registered.
+    /// Entities whose parent is left out are saved without a parent.
+    pub fn save(&self, world: &World) -> Result<SavedWorld> {
+        let mut saved = SavedWorld::default();
+
+        for name in world.entity_data.registered_names() {
+            if !is_built_in(name) {
+                saved.components.push(name.clone());
+            }
+        }
+        saved.components.sort();
+
+        let ids: Vec<Entity> = world
+            .query_typed::<(&Entity,)>([ENTITY_ID])?
+            .map(|(entity,)| *entity)
+            .collect();
+        let saved_ids: HashSet<Entity> = ids.iter().copied().collect();
+        for id in ids {
+            let mut components = BTreeMap::new();
+            for (name, component) in world.entity_data.components(id)? {
+                if is_built_in(name) {
+                    continue;
+                }
+                if let Some(handler) = self.handlers.get(&component.type_id()) {
+                    components.insert(
+                        name.clone(),
+                        SavedValue {
+                            type_key: handler.key.clone(),
+                            value: (handler.save_component)(component)?,
+                        },
+                    );
+                }
+            }
+
+            saved.entities.push(SavedEntity {
+                id,
+                parent: world.parent(id).filter(|parent| saved_ids.contains(parent)),
+                components,
+            });
+        }
+
+        for (name, resource) in world.resources.iter() {
+            let resource = resource.borrow();
+            if let Some(handler) = self.handlers.get(&resource.type_id()) {
+                saved.resources.insert(
+                    name.clone(),
+                    SavedValue {
+                        type_key: handler.key.clone(),
+                        value: (handler.save_resource)(&resource)?,
+                    },
+                );
+            }
+        }
+
+        Ok(saved)
+    }
+
+    /// Add everything from the save into the world. Entities are spawned fresh, so any
+    /// entities stored in components or resources are remapped to the new entities. Returns
+    /// the map from the saved entities to the spawned ones.
+    ///
+    /// The save is checked before anything is spawned, and if loading still fails partway the
+    /// spawned entities and the names registered for the save are removed again, so none of a
+    /// save that cannot be loaded is left in the world.
+    pub fn load(&self, world: &mut World, saved: SavedWorld) -> Result<EntityMap> {
+        self.validate(world, &saved)?;
+
+        let mut registered = vec![];
+        let mut entities = EntityMap::default();
+        if let Err(error) = self.spawn_saved(world, saved, &mut registered, &mut entities) {
+            for entity in entities.entities.values() {
+                world.despawn(*entity)?;
+            }
+            for name in &registered {
+                world.entity_data.unregister(name);
+            }
+            return Err(error);
+        }
+
+        Ok(entities)
+    }
+
+    /// Register the names in the save and spawn its entities, remembering what was added so
+    /// that it can be removed if loading fails
+    fn spawn_saved(
+        &self,
+        world: &mut World,
+        saved: SavedWorld,
+        registered: &mut Vec<String>,
+        entities: &mut EntityMap,
+    ) -> Result<()> {
+        for name in &saved.components {
+            if !world.is_registered(name) {
+                world.register(name)?;
+                registered.push(name.clone());
+            }
+        }
+
+        for saved_entity in &saved.entities {
+            let entity = world.spawn_entity()?.entity();
+            entities.entities.insert(saved_entity.id, entity);
+        }
+
+        let parents = saved
+            .entities
+            .iter()
+            .filter_map(|saved_entity| Some((saved_entity.id, saved_entity.parent?)))
+            .map(|(child, parent)| Ok((entities.get(child)?, entities.get(parent)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let loaded = self.load_values(saved, entities)?;
+
+        for (entity, name, insert) in loaded.components {
+            insert(world, LoadTarget::Component(entity, &name))?;
+        }
+        for (child, parent) in parents {
+            world.set_parent(child, parent)?;
+        }
+        for (name, insert) in loaded.resources {
+            insert(world, LoadTarget::Resource(&name))?;
+        }
+
+        Ok(())
+    }
+
+    /// Check that every type in the save is registered, that every component can be stored
+    /// in the world, and that every parent is in the save. Names that are not registered yet
+    /// must hold the same type on every entity in the save.
+    fn validate(&self, world: &World, saved: &SavedWorld) -> Result<()> {
+        let saved_ids: HashSet<Entity> = saved.entities.iter().map(|entity| entity.id).collect();
+        let mut new_types: HashMap<&str, &TypeHandler> = HashMap::new();
+
+        for saved_entity in &saved.entities {
+            for (name, saved_value) in &saved_entity.components {
+                let handler = self.handler(&saved_value.type_key)?;
+                if is_built_in(name) {
+                    return Err(BbEcsError::BuiltInComponent(name.clone()));
+                }
+                if world.is_registered(name) {
+                    world
+                        .entity_data
+                        .type_matches(name, handler.type_id, handler.type_name)?;
+                } else if !saved.components.contains(name) {
+                    return Err(BbEcsError::NeedToRegister);
+                } else {
+                    let first = new_types.entry(name).or_insert(handler);
+                    if first.type_id != handler.type_id {
+                        return Err(BbEcsError::ComponentTypeMismatch(
+                            name.clone(),
+                            first.type_name,
+                            handler.type_name,
+                        ));
+                    }
+                }
+            }
+            if let Some(parent) = saved_entity.parent {
+                if !saved_ids.contains(&parent) {
+                    return Err(BbEcsError::EntityNotSaved(parent));
+                }
+            }
+        }
+
+        for saved_value in saved.resources.values() {
+            self.handler(&saved_value.type_key)?;
+        }
+        Ok(())
+    }
+
+    /// Load every value in the save without putting any of them into the world yet
+    fn load_values(&self, saved: SavedWorld, entities: &EntityMap) -> Result<LoadedValues> {
+        let mut loaded = LoadedValues::default();
+        for saved_entity in saved.entities {
+            let entity = entities.get(saved_entity.id)?;
+            for (name, saved_value) in saved_entity.components {
+                let handler = self.handler(&saved_value.type_key)?;
+                let insert = (handler.load)(saved_value.value, entities)?;
+                loaded.components.push((entity, name, insert));
+            }
+        }
+
+        for (name, saved_value) in saved.resources {
+            let handler = self.handler(&saved_value.type_key)?;
+            let insert = (handler.load)(saved_value.value, entities)?;
+            loaded.resources.push((name, insert));
+        }
+        Ok(loaded)
+    }
+
+    pub fn to_json(&self, world: &World) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.save(world)?)?)
+    }
+
+    pub fn from_json(&self, world: &mut World, data: &str) -> Result<EntityMap> {
+        self.load(world, serde_json::from_str(data)?)
+    }
+
+    pub fn to_ron(&self, world: &World) -> Result<String> {
+        Ok(ron::ser::to_string_pretty(
+            &self.save(world)?,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    pub fn from_ron(&self, world: &mut World, data: &str) -> Result<EntityMap> {
+        self.load(world, ron::from_str(data)?)
+    }
+
+    fn handler(&self, key: &str) -> Result<&TypeHandler> {
+        self.type_ids
+            .get(key)
+            .and_then(|type_id| self.handlers.get(type_id))
+            .ok_or_else(|| BbEcsError::UnknownSavedType(key.to_owned()))
+    }
+}
+
+impl Default for WorldSerializer {
+    fn default() -> Self {
+        let mut serializer = Self {
+            handlers: HashMap::new(),
+            type_ids: HashMap::new(),
+        };
+
+        serializer
+            .register::<bool>("bool")
+            .register::<f32>("f32")
+            .register::<f64>("f64")
+            .register::<i32>("i32")
+            .register::<i64>("i64")
+            .register::<u32>("u32")
+            .register::<u64>("u64")
+            .register::<usize>("usize")
+            .register::<String>("String")
+            .register::<Point>("Point")
+            .register::<Transform>("Transform")
+            .register_with(
+                "Entity",
+                |entity: &Entity| Ok(serde_json::to_value(entity)?),
+                |value, entities| Ok(entities.get_or_dangling(serde_json::from_value(value)?)),
+            )
+            .register_with(
+                "Color",
+                |color: &Color| Ok(serde_json::to_value(<[f32; 4]>::from(*color))?),
+                |value, _| Ok(Color::from(serde_json::from_value::<[f32; 4]>(value)?)),
+            );
+
+        serializer
+    }
+}
+
+fn is_built_in(name: &str) -> bool {
+    name == TO_BE_DELETED || name == ENTITY_ID
+}
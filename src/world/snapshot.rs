@@ -0,0 +1,798 @@
+//! Binary snapshots of a world that are cheap enough to take every frame, for rewinding and
+//! replaying. A snapshot holds the components of every entity, including the flags for
+//! entities waiting to be deleted, along with the state of the entity allocator, the
+//! hierarchy and the resources. Restoring a snapshot puts the same world back the way it was,
+//! so every entity handle from the time of the snapshot is valid again.
+//!
+//! Only types with a codec registered in the `Snapshotter` are captured. Components of other
+//! types, such as ggez meshes, are kept on their entities when restoring as long as the entity
+//! is in the snapshot, and resources of other types are left alone.
+
+use std::any::TypeId;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryInto;
+
+use ggez::graphics::Color;
+
+use crate::components::{CastComponents, ComponentData};
+use crate::data_types::point::Point;
+use crate::data_types::transform::Transform;
+use crate::errors::{BbEcsError, Result};
+use crate::resources::resource::{Resource, ResourceCast};
+
+use super::hierarchy::Hierarchy;
+use super::{DataWrapper, Entity, Storable, World};
+
+/// The version of the byte format written by `Snapshot::to_bytes`
+pub const SNAPSHOT_VERSION: u32 = 2;
+const MAGIC: &[u8; 4] = b"BBSS";
+
+/// Types that can be written into a snapshot and read back again
+///
+/// ```
+/// use bbecs::world::snapshot::{Encode, Reader};
+///
+/// struct Health(u32);
+///
+/// impl Encode for Health {
+///     fn encode(&self, buffer: &mut Vec<u8>) {
+///         self.0.encode(buffer);
+///     }
+///
+///     fn decode(reader: &mut Reader<'_>) -> bbecs::errors::Result<Self> {
+///         Ok(Health(u32::decode(reader)?))
+///     }
+/// }
+/// ```
+pub trait Encode: Sized {
+    fn encode(&self, buffer: &mut Vec<u8>);
+    fn decode(reader: &mut Reader<'_>) -> Result<Self>;
+}
+
+/// Reads encoded values back out of a snapshot's bytes
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Take the next bytes, failing if there are not enough of them left
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.bytes.len() {
+            return Err(BbEcsError::SnapshotCorrupted("unexpected end of data"));
+        }
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+}
+
+macro_rules! encode_numbers {
+    ($($number:ty),*) => {
+        $(
+            impl Encode for $number {
+                fn encode(&self, buffer: &mut Vec<u8>) {
+                    buffer.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+                    let bytes = reader.read_bytes(std::mem::size_of::<Self>())?;
+                    let bytes = bytes
+                        .try_into()
+                        .map_err(|_| BbEcsError::SnapshotCorrupted("truncated number"))?;
+                    Ok(Self::from_le_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+encode_numbers!(u8, u32, u64, i32, i64, f32, f64);
+
+impl Encode for usize {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        (*self as u64).encode(buffer);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+        u64::decode(reader)?
+            .try_into()
+            .map_err(|_| BbEcsError::SnapshotCorrupted("length too large"))
+    }
+}
+
+impl Encode for bool {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        (*self as u8).encode(buffer);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+        match u8::decode(reader)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(BbEcsError::SnapshotCorrupted("invalid bool")),
+        }
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        self.len().encode(buffer);
+        buffer.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+        let len = usize::decode(reader)?;
+        String::from_utf8(reader.read_bytes(len)?.to_vec())
+            .map_err(|_| BbEcsError::SnapshotCorrupted("invalid string"))
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        self.is_some().encode(buffer);
+        if let Some(value) = self {
+            value.encode(buffer);
+        }
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+        if bool::decode(reader)? {
+            Ok(Some(T::decode(reader)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        self.len().encode(buffer);
+        for value in self {
+            value.encode(buffer);
+        }
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+        let len = usize::decode(reader)?;
+        (0..len).map(|_| T::decode(reader)).collect()
+    }
+}
+
+impl<K: Encode + Ord, V: Encode> Encode for BTreeMap<K, V> {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        self.len().encode(buffer);
+        for (key, value) in self {
+            key.encode(buffer);
+            value.encode(buffer);
+        }
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+        let len = usize::decode(reader)?;
+        (0..len)
+            .map(|_| Ok((K::decode(reader)?, V::decode(reader)?)))
+            .collect()
+    }
+}
+
+impl Encode for Entity {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        self.index().encode(buffer);
+        self.generation().encode(buffer);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+        Ok(Entity::new(u32::decode(reader)?, u32::decode(reader)?))
+    }
+}
+
+impl Encode for Point {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        self.x.encode(buffer);
+        self.y.encode(buffer);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+        Ok(Point::new(f32::decode(reader)?, f32::decode(reader)?))
+    }
+}
+
+impl Encode for Transform {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        self.position.encode(buffer);
+        self.rotation.encode(buffer);
+        self.scale.encode(buffer);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+        Ok(Transform {
+            position: Point::decode(reader)?,
+            rotation: f32::decode(reader)?,
+            scale: Point::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for Color {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        for channel in <[f32; 4]>::from(*self).iter() {
+            channel.encode(buffer);
+        }
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+        Ok(Color::new(
+            f32::decode(reader)?,
+            f32::decode(reader)?,
+            f32::decode(reader)?,
+            f32::decode(reader)?,
+        ))
+    }
+}
+
+/// Everything captured from a world at one point in time
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    generations: Vec<u32>,
+    free_indexes: Vec<u32>,
+    archetypes: Vec<SnapshotArchetype>,
+    children: BTreeMap<Entity, Vec<Entity>>,
+    resources: BTreeMap<String, SnapshotResource>,
+}
+
+/// The entities that had the same set of components, with one column for each component
+#[derive(Debug, Clone, Default, PartialEq)]
+struct SnapshotArchetype {
+    entities: Vec<Entity>,
+    columns: Vec<SnapshotColumn>,
+}
+
+/// The encoded values of one component for every entity in an archetype, written back to
+/// back. `ends` holds the offset where the value of each entity stops.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct SnapshotColumn {
+    name: String,
+    ends: Vec<u32>,
+    bytes: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct SnapshotResource {
+    key: String,
+    bytes: Vec<u8>,
+}
+
+impl Snapshot {
+    /// Every entity that was alive when the snapshot was taken
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.archetypes
+            .iter()
+            .flat_map(|archetype| archetype.entities.iter().copied())
+    }
+
+    /// Where the components of each entity are stored, as an archetype and a row
+    fn rows(&self) -> HashMap<Entity, (&SnapshotArchetype, usize)> {
+        self.archetypes
+            .iter()
+            .flat_map(|archetype| {
+                archetype
+                    .entities
+                    .iter()
+                    .enumerate()
+                    .map(move |(row, entity)| (*entity, (archetype, row)))
+            })
+            .collect()
+    }
+
+    /// Write the snapshot out as bytes, starting with a header holding `SNAPSHOT_VERSION`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = MAGIC.to_vec();
+        SNAPSHOT_VERSION.encode(&mut buffer);
+        self.encode(&mut buffer);
+        buffer
+    }
+
+    /// Read a snapshot from bytes written by `to_bytes`. Snapshots written with a different
+    /// version of the format cannot be read.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(bytes);
+        if reader.read_bytes(MAGIC.len())? != MAGIC {
+            return Err(BbEcsError::SnapshotCorrupted("missing snapshot header"));
+        }
+        let version = u32::decode(&mut reader)?;
+        if version != SNAPSHOT_VERSION {
+            return Err(BbEcsError::SnapshotVersion(version, SNAPSHOT_VERSION));
+        }
+
+        let snapshot = Self::decode(&mut reader)?;
+        if !reader.is_empty() {
+            return Err(BbEcsError::SnapshotCorrupted(
+                "unexpected data after the end",
+            ));
+        }
+        Ok(snapshot)
+    }
+
+    /// Find what changed between this snapshot and a later one
+    ///
+    /// ```
+    /// use bbecs::world::snapshot::Snapshotter;
+    /// use bbecs::world::World;
+    ///
+    /// let mut world = World::new();
+    /// world.register("health").unwrap();
+    /// let ship = world
+    ///     .spawn_entity()
+    ///     .unwrap()
+    ///     .with_component("health", 10_u32)
+    ///     .unwrap()
+    ///     .entity();
+    ///
+    /// let snapshotter = Snapshotter::new();
+    /// let before = snapshotter.take(&world).unwrap();
+    /// world.insert_component(ship, "health", 7_u32).unwrap();
+    /// let after = snapshotter.take(&world).unwrap();
+    ///
+    /// let diff = before.diff(&after);
+    /// assert_eq!(diff.changed, vec![(ship, "health".to_owned())]);
+    /// assert!(diff.spawned.is_empty());
+    /// ```
+    pub fn diff(&self, later: &Snapshot) -> SnapshotDiff {
+        let mut diff = SnapshotDiff::default();
+
+        let rows = self.rows();
+        let later_rows = later.rows();
+        for (entity, saved) in &rows {
+            match later_rows.get(entity) {
+                Some(later_saved) => diff_components(*entity, *saved, *later_saved, &mut diff),
+                None => diff.despawned.push(*entity),
+            }
+        }
+        diff.spawned = later
+            .entities()
+            .filter(|entity| !rows.contains_key(entity))
+            .collect();
+        diff.spawned.sort();
+        diff.despawned.sort();
+        diff.changed.sort();
+
+        let names = self.resources.keys().chain(later.resources.keys());
+        for name in names {
+            if self.resources.get(name) != later.resources.get(name)
+                && !diff.resources.contains(name)
+            {
+                diff.resources.push(name.clone());
+            }
+        }
+
+        diff
+    }
+}
+
+impl Encode for Snapshot {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        self.generations.encode(buffer);
+        self.free_indexes.encode(buffer);
+        self.archetypes.encode(buffer);
+        self.children.encode(buffer);
+        self.resources.encode(buffer);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+        Ok(Self {
+            generations: Vec::decode(reader)?,
+            free_indexes: Vec::decode(reader)?,
+            archetypes: Vec::decode(reader)?,
+            children: BTreeMap::decode(reader)?,
+            resources: BTreeMap::decode(reader)?,
+        })
+    }
+}
+
+impl SnapshotArchetype {
+    fn column(&self, name: &str) -> Option<&SnapshotColumn> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+}
+
+impl Encode for SnapshotArchetype {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        self.entities.encode(buffer);
+        self.columns.encode(buffer);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+        let archetype = Self {
+            entities: Vec::decode(reader)?,
+            columns: Vec::decode(reader)?,
+        };
+        let rows = archetype.entities.len();
+        if archetype
+            .columns
+            .iter()
+            .any(|column| column.ends.len() != rows)
+        {
+            return Err(BbEcsError::SnapshotCorrupted(
+                "column length does not match the entities",
+            ));
+        }
+        Ok(archetype)
+    }
+}
+
+impl SnapshotColumn {
+    /// The encoded value of the entity in the row
+    fn value(&self, row: usize) -> &[u8] {
+        let start = match row {
+            0 => 0,
+            _ => self.ends[row - 1] as usize,
+        };
+        &self.bytes[start..self.ends[row] as usize]
+    }
+}
+
+impl Encode for SnapshotColumn {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        self.name.encode(buffer);
+        self.ends.encode(buffer);
+        self.bytes.encode(buffer);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+        let column = Self {
+            name: String::decode(reader)?,
+            ends: Vec::decode(reader)?,
+            bytes: Vec::decode(reader)?,
+        };
+        let ordered = column.ends.windows(2).all(|ends| ends[0] <= ends[1]);
+        let last = column.ends.last().map_or(0, |end| *end as usize);
+        if !ordered || last != column.bytes.len() {
+            return Err(BbEcsError::SnapshotCorrupted("invalid column offsets"));
+        }
+        Ok(column)
+    }
+}
+
+impl Encode for SnapshotResource {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        self.key.encode(buffer);
+        self.bytes.encode(buffer);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+        Ok(Self {
+            key: String::decode(reader)?,
+            bytes: Vec::decode(reader)?,
+        })
+    }
+}
+
+/// The differences between two snapshots. Components that were added, removed or given a
+/// new value on entities in both snapshots are listed as changed, as are resources.
+#[derive(Debug, Default, PartialEq)]
+pub struct SnapshotDiff {
+    pub spawned: Vec<Entity>,
+    pub despawned: Vec<Entity>,
+    pub changed: Vec<(Entity, String)>,
+    pub resources: Vec<String>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.spawned.is_empty()
+            && self.despawned.is_empty()
+            && self.changed.is_empty()
+            && self.resources.is_empty()
+    }
+}
+
+fn diff_components(
+    entity: Entity,
+    (saved, row): (&SnapshotArchetype, usize),
+    (later, later_row): (&SnapshotArchetype, usize),
+    diff: &mut SnapshotDiff,
+) {
+    for column in &saved.columns {
+        let later_value = later
+            .column(&column.name)
+            .map(|later_column| later_column.value(later_row));
+        if later_value != Some(column.value(row)) {
+            diff.changed.push((entity, column.name.clone()));
+        }
+    }
+    for column in &later.columns {
+        if saved.column(&column.name).is_none() {
+            diff.changed.push((entity, column.name.clone()));
+        }
+    }
+}
+
+type EncodeComponentFn = fn(&ComponentData, &mut Vec<u8>) -> Result<()>;
+type DecodeComponentFn = fn(&mut Reader<'_>) -> Result<ComponentData>;
+type EncodeResourceFn = fn(&Resource, &mut Vec<u8>) -> Result<()>;
+type DecodeResourceFn = fn(&mut Reader<'_>) -> Result<Resource>;
+
+struct Codec {
+    key: String,
+    encode_component: EncodeComponentFn,
+    decode_component: DecodeComponentFn,
+    encode_resource: EncodeResourceFn,
+    decode_resource: DecodeResourceFn,
+}
+
+fn encode_component<T: Encode + Storable>(
+    component: &ComponentData,
+    buffer: &mut Vec<u8>,
+) -> Result<()> {
+    let wrapper: &DataWrapper<T> = component.cast()?;
+    wrapper.borrow().encode(buffer);
+    Ok(())
+}
+
+fn encode_column(name: &str, column: &[ComponentData], codec: &Codec) -> Result<SnapshotColumn> {
+    let mut saved = SnapshotColumn {
+        name: name.to_owned(),
+        ..SnapshotColumn::default()
+    };
+    for component in column {
+        (codec.encode_component)(component, &mut saved.bytes)?;
+        let end = saved
+            .bytes
+            .len()
+            .try_into()
+            .map_err(|_| BbEcsError::SnapshotCorrupted("component column is too large"))?;
+        saved.ends.push(end);
+    }
+    Ok(saved)
+}
+
+fn decode_component<T: Encode + Storable>(reader: &mut Reader<'_>) -> Result<ComponentData> {
+    Ok(ComponentData::new(T::decode(reader)?))
+}
+
+fn encode_resource<T: Encode + Storable>(resource: &Resource, buffer: &mut Vec<u8>) -> Result<()> {
+    let data: &T = resource.cast()?;
+    data.encode(buffer);
+    Ok(())
+}
+
+fn decode_resource<T: Encode + Storable>(reader: &mut Reader<'_>) -> Result<Resource> {
+    Ok(Resource::new(T::decode(reader)?))
+}
+
+/// Takes snapshots of worlds and restores them, knowing how to encode each registered type.
+/// The types that bbecs provides, along with the primitive types and `String`, are
+/// registered already, under the names of the types such as `"f32"` and `"Point"`.
+///
+/// ```
+/// use bbecs::data_types::point::Point;
+/// use bbecs::world::snapshot::Snapshotter;
+/// use bbecs::world::World;
+///
+/// let mut world = World::new();
+/// world.register("location").unwrap();
+/// let ship = world
+///     .spawn_entity()
+///     .unwrap()
+///     .with_component("location", Point::new(0.0, 0.0))
+///     .unwrap()
+///     .entity();
+///
+/// let snapshotter = Snapshotter::new();
+/// let snapshot = snapshotter.take(&world).unwrap();
+///
+/// world.despawn(ship).unwrap();
+/// snapshotter.restore(&mut world, &snapshot).unwrap();
+/// assert!(world.is_alive(ship));
+/// ```
+pub struct Snapshotter {
+    codecs: HashMap<TypeId, Codec>,
+    type_ids: HashMap<String, TypeId>,
+}
+
+impl Snapshotter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow components and resources of this type to be captured in snapshots. The key is
+    /// written into snapshots to say what type a resource is, so it needs to stay the same for
+    /// snapshots to be restored after the program changes.
+    ///
+    /// ```
+    /// use bbecs::world::snapshot::{Encode, Reader, Snapshotter};
+    ///
+    /// struct Health(u32);
+    ///
+    /// impl Encode for Health {
+    ///     fn encode(&self, buffer: &mut Vec<u8>) {
+    ///         self.0.encode(buffer);
+    ///     }
+    ///
+    ///     fn decode(reader: &mut Reader<'_>) -> bbecs::errors::Result<Self> {
+    ///         Ok(Health(u32::decode(reader)?))
+    ///     }
+    /// }
+    ///
+    /// let mut snapshotter = Snapshotter::new();
+    /// snapshotter.register::<Health>("Health");
+    /// ```
+    pub fn register<T: Encode + Storable>(&mut self, key: &str) -> &mut Self {
+        let codec = Codec {
+            key: key.to_owned(),
+            encode_component: encode_component::<T>,
+            decode_component: decode_component::<T>,
+            encode_resource: encode_resource::<T>,
+            decode_resource: decode_resource::<T>,
+        };
+
+        self.type_ids.insert(key.to_owned(), TypeId::of::<T>());
+        self.codecs.insert(TypeId::of::<T>(), codec);
+        self
+    }
+
+    /// Capture the world as it is now
+    pub fn take(&self, world: &World) -> Result<Snapshot> {
+        let entities = world.entity_data.entities();
+        let mut snapshot = Snapshot {
+            generations: entities.generations().to_vec(),
+            free_indexes: entities.free_indexes().to_vec(),
+            ..Snapshot::default()
+        };
+
+        for archetype in world.entity_data.archetypes() {
+            if archetype.entities().is_empty() {
+                continue;
+            }
+
+            let mut columns = vec![];
+            for (name, column) in archetype.columns() {
+                let codec = match column.first() {
+                    Some(first) => self.codecs.get(&first.type_id()),
+                    None => None,
+                };
+                if let Some(codec) = codec {
+                    columns.push(encode_column(name, column, codec)?);
+                }
+            }
+            columns.sort_by(|a, b| a.name.cmp(&b.name));
+
+            for entity in archetype.entities() {
+                let children = world.children(*entity);
+                if !children.is_empty() {
+                    snapshot.children.insert(*entity, children.to_vec());
+                }
+            }
+            snapshot.archetypes.push(SnapshotArchetype {
+                entities: archetype.entities().to_vec(),
+                columns,
+            });
+        }
+
+        for (name, resource) in world.resources.iter() {
+            let resource = resource.borrow();
+            if let Some(codec) = self.codecs.get(&resource.type_id()) {
+                let mut bytes = vec![];
+                (codec.encode_resource)(&resource, &mut bytes)?;
+                let key = codec.key.to_owned();
+                snapshot
+                    .resources
+                    .insert(name.clone(), SnapshotResource { key, bytes });
+            }
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Put the world back the way it was when the snapshot was taken. Entities spawned since
+    /// then are removed, and despawned entities come back with the same handles. Restored
+    /// components are marked as added and changed at the current change tick, which is not
+    /// rewound. Nothing in the world is changed if the snapshot cannot be decoded.
+    pub fn restore(&self, world: &mut World, snapshot: &Snapshot) -> Result<()> {
+        let tick = world.change_tick();
+        let mut restored = vec![];
+        for archetype in &snapshot.archetypes {
+            let mut components: Vec<HashMap<String, ComponentData>> =
+                archetype.entities.iter().map(|_| HashMap::new()).collect();
+            for column in &archetype.columns {
+                let type_id = world
+                    .entity_data
+                    .registered_type(&column.name)
+                    .ok_or_else(|| BbEcsError::ComponentNotFound(column.name.clone()))?;
+                let codec = self
+                    .codecs
+                    .get(&type_id)
+                    .ok_or(BbEcsError::SnapshotCorrupted(
+                        "component type is not registered",
+                    ))?;
+
+                for (row, components) in components.iter_mut().enumerate() {
+                    let mut reader = Reader::new(column.value(row));
+                    let component = (codec.decode_component)(&mut reader)?;
+                    component.ticks().set_added(tick);
+                    components.insert(column.name.clone(), component);
+                }
+            }
+            restored.extend(archetype.entities.iter().copied().zip(components));
+        }
+
+        let mut resources = vec![];
+        for (name, saved) in &snapshot.resources {
+            let codec = self
+                .type_ids
+                .get(saved.key.as_str())
+                .and_then(|type_id| self.codecs.get(type_id))
+                .ok_or_else(|| BbEcsError::UnknownSavedType(saved.key.clone()))?;
+            let resource = (codec.decode_resource)(&mut Reader::new(&saved.bytes))?;
+            resources.push((name.clone(), resource));
+        }
+
+        let alive: HashSet<Entity> = snapshot.entities().collect();
+        let mut kept: HashMap<Entity, HashMap<String, ComponentData>> = world
+            .entity_data
+            .take_all()
+            .into_iter()
+            .filter(|(entity, _)| alive.contains(entity))
+            .map(|(entity, mut components)| {
+                components.retain(|_, component| !self.codecs.contains_key(&component.type_id()));
+                (entity, components)
+            })
+            .collect();
+        for (entity, components) in restored.iter_mut() {
+            components.extend(kept.remove(entity).unwrap_or_default());
+        }
+
+        world.entity_data.restore(
+            snapshot.generations.clone(),
+            snapshot.free_indexes.clone(),
+            restored,
+        )?;
+
+        world.hierarchy = Hierarchy::default();
+        for (entity, children) in &snapshot.children {
+            for child in children {
+                world.hierarchy.set_parent(*child, *entity);
+            }
+        }
+
+        for (name, resource) in resources {
+            world.resources.insert(name, resource);
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Snapshotter {
+    fn default() -> Self {
+        let mut snapshotter = Self {
+            codecs: HashMap::new(),
+            type_ids: HashMap::new(),
+        };
+
+        snapshotter
+            .register::<bool>("bool")
+            .register::<f32>("f32")
+            .register::<f64>("f64")
+            .register::<i32>("i32")
+            .register::<i64>("i64")
+            .register::<u32>("u32")
+            .register::<u64>("u64")
+            .register::<usize>("usize")
+            .register::<String>("String")
+            .register::<Point>("Point")
+            .register::<Transform>("Transform")
+            .register::<Entity>("Entity")
+            .register::<Color>("Color");
+
+        snapshotter
+    }
+}
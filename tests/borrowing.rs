@@ -0,0 +1,152 @@
+use bbecs::data_types::point::Point;
+use bbecs::errors::{BbEcsError, BorrowAccess, Result};
+use bbecs::resources::resource::ResourceCast;
+use bbecs::world::{World, WorldMethods};
+use bbecs::{get_resource_mut, try_get_resource, try_get_resource_mut};
+
+fn is_conflict(error: &BbEcsError, expected_name: &str, expected_access: BorrowAccess) -> bool {
+    matches!(
+        error,
+        BbEcsError::BorrowConflict(name, access)
+            if name == expected_name && *access == expected_access
+    )
+}
+
+fn add_point(world: &World) -> Result<u32> {
+    let score: &mut u32;
+    try_get_resource_mut!(score, world, "score");
+    *score += 1;
+    Ok(*score)
+}
+
+fn read_score(world: &World) -> Result<u32> {
+    let score: &u32;
+    try_get_resource!(score, world, "score");
+    Ok(*score)
+}
+
+#[test]
+fn overlapping_resource_borrows_fail_instead_of_panicking() -> Result<()> {
+    let mut world = World::new();
+    world.add_resource("score".to_owned(), 0_u32);
+    assert_eq!(add_point(&world)?, 1);
+
+    {
+        let score: &mut u32;
+        get_resource_mut!(score, world, "score");
+
+        assert!(is_conflict(
+            &read_score(&world).unwrap_err(),
+            "score",
+            BorrowAccess::Read
+        ));
+        assert!(is_conflict(
+            &add_point(&world).unwrap_err(),
+            "score",
+            BorrowAccess::Write
+        ));
+        *score += 1;
+    }
+
+    assert_eq!(read_score(&world)?, 2);
+    Ok(())
+}
+
+#[test]
+fn missing_resources_are_still_reported() -> Result<()> {
+    let mut world = World::new();
+    world.add_resource("score".to_owned(), 0_u32);
+
+    let error = world.try_borrow_resource("lives").unwrap_err();
+    assert!(matches!(
+        error,
+        BbEcsError::ResourceNotFound(name) if name == "lives"
+    ));
+    Ok(())
+}
+
+#[test]
+fn overlapping_component_borrows_fail_instead_of_panicking() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("size")?;
+    let entity = world
+        .spawn_entity()?
+        .with_component("location", Point::new(0.0, 0.0))?
+        .with_component("size", 5.0_f32)?
+        .entity();
+
+    let size = world.try_borrow_component::<f32>(entity, "size")?;
+    let error = world
+        .try_borrow_component_mut::<f32>(entity, "size")
+        .err()
+        .unwrap();
+    assert!(is_conflict(&error, "size", BorrowAccess::Write));
+    assert_eq!(*world.try_borrow_component::<f32>(entity, "size")?, 5.0);
+    drop(size);
+
+    let last_run = world.change_tick();
+    world.increment_change_tick();
+    let mut size = world.try_borrow_component_mut::<f32>(entity, "size")?;
+    *size += 1.0;
+    drop(size);
+
+    let component = world.query(vec!["size"])?["size"][0];
+    assert!(component.ticks().is_changed_since(last_run));
+    assert_eq!(*world.try_borrow_component::<f32>(entity, "size")?, 6.0);
+    Ok(())
+}
+
+#[test]
+fn typed_queries_can_report_conflicting_borrows() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("size")?;
+    let entity = world
+        .spawn_entity()?
+        .with_component("location", Point::new(0.0, 0.0))?
+        .with_component("size", 5.0_f32)?
+        .entity();
+
+    let held = world.try_borrow_component::<f32>(entity, "size")?;
+    let mut query = world.query_typed::<(&Point, &mut f32)>(["location", "size"])?;
+    let error = query.try_next().unwrap().err().unwrap();
+    assert!(is_conflict(&error, "size", BorrowAccess::Write));
+    assert!(query.try_next().is_none());
+    drop(held);
+
+    let mut query = world.query_typed::<(&Point, &mut f32)>(["location", "size"])?;
+    let (location, mut size) = query.try_next().unwrap()?;
+    *size += location.x;
+    Ok(())
+}
+
+#[test]
+fn sending_events_that_are_borrowed_fails_instead_of_panicking() -> Result<()> {
+    let mut world = World::new();
+    world.add_events::<u32>("points scored");
+
+    let held = world.try_borrow_resource("points scored")?;
+    let error = world.send_event("points scored", 10_u32).unwrap_err();
+    assert!(is_conflict(&error, "points scored", BorrowAccess::Write));
+    drop(held);
+
+    world.send_event("points scored", 10_u32)?;
+    Ok(())
+}
+
+#[test]
+fn flagging_an_entity_that_is_borrowed_fails_instead_of_panicking() -> Result<()> {
+    let mut world = World::new();
+    let entity = world.spawn_entity()?.entity();
+
+    let held = world.try_borrow_component::<bool>(entity, "to be deleted")?;
+    let error = world.delete_by_id(entity).unwrap_err();
+    assert!(is_conflict(&error, "to be deleted", BorrowAccess::Write));
+    drop(held);
+
+    world.delete_by_id(entity)?;
+    world.update()?;
+    assert!(!world.is_alive(entity));
+    Ok(())
+}
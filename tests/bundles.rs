@@ -0,0 +1,155 @@
+use bbecs::components::ComponentData;
+use bbecs::data_types::point::Point;
+use bbecs::errors::{BbEcsError, Result};
+use bbecs::world::bundle::Bundle;
+use bbecs::world::commands::Commands;
+use bbecs::world::{Entity, World, ENTITY_ID};
+use ggez::graphics::Color;
+
+struct Asteroid {
+    location: Point,
+    velocity: Point,
+    size: f32,
+    color: Color,
+}
+
+impl Bundle for Asteroid {
+    fn into_components(self) -> Vec<(String, ComponentData)> {
+        vec![
+            ("location".to_owned(), ComponentData::new(self.location)),
+            ("velocity".to_owned(), ComponentData::new(self.velocity)),
+            ("size".to_owned(), ComponentData::new(self.size)),
+            ("color".to_owned(), ComponentData::new(self.color)),
+        ]
+    }
+}
+
+fn spawn_error<B: Bundle>(world: &mut World, bundle: B) -> BbEcsError {
+    match world.spawn_bundle(bundle) {
+        Ok(_) => panic!("expected the bundle to fail to spawn"),
+        Err(error) => error,
+    }
+}
+
+fn entity_count(world: &World) -> Result<usize> {
+    Ok(world.query_typed::<(&Entity,)>([ENTITY_ID])?.count())
+}
+
+#[test]
+fn tuples_of_components_can_be_spawned_as_a_bundle() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("velocity")?;
+    world.register("size")?;
+
+    let entity = world
+        .spawn_bundle((
+            ("location", Point::new(1.0, 2.0)),
+            ("velocity", Point::new(0.0, 1.0)),
+            ("size", 5.0_f32),
+        ))?
+        .entity();
+
+    let (id, location, velocity, size) = world
+        .query_typed::<(&Entity, &Point, &Point, &f32)>([
+            ENTITY_ID, "location", "velocity", "size",
+        ])?
+        .next()
+        .unwrap();
+    assert_eq!(*id, entity);
+    assert_eq!(*location, Point::new(1.0, 2.0));
+    assert_eq!(*velocity, Point::new(0.0, 1.0));
+    assert_eq!(*size, 5.0);
+    Ok(())
+}
+
+#[test]
+fn structs_can_be_spawned_as_a_bundle() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("velocity")?;
+    world.register("size")?;
+    world.register("color")?;
+    let asteroid = Asteroid {
+        location: Point::new(10.0, 10.0),
+        velocity: Point::new(-1.0, 0.0),
+        size: 25.0,
+        color: Color::new(0.5, 0.5, 0.5, 1.0),
+    };
+
+    let entity = world.spawn_bundle(asteroid)?.entity();
+
+    assert!(world.has_component(entity, "color")?);
+    assert!(world.has_component(entity, "velocity")?);
+    Ok(())
+}
+
+#[test]
+fn more_components_can_be_chained_onto_a_bundle() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("size")?;
+    let parent = world.spawn_entity()?.entity();
+
+    let entity = world
+        .spawn_bundle((("location", Point::new(0.0, 0.0)),))?
+        .with_component("size", 1.0_f32)?
+        .with_parent(parent)?
+        .entity();
+
+    assert!(world.has_component(entity, "size")?);
+    assert_eq!(world.parent(entity), Some(parent));
+    Ok(())
+}
+
+#[test]
+fn invalid_bundles_spawn_nothing() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("size")?;
+    world.spawn_entity()?.with_component("size", 5.0_f32)?;
+
+    let error = spawn_error(
+        &mut world,
+        (("location", Point::new(0.0, 0.0)), ("size", 5_u32)),
+    );
+    assert!(matches!(
+        error,
+        BbEcsError::ComponentTypeMismatch(name, _, _) if name == "size"
+    ));
+
+    let error = spawn_error(
+        &mut world,
+        (("location", Point::new(0.0, 0.0)), ("health", 5_u32)),
+    );
+    assert!(matches!(error, BbEcsError::NeedToRegister));
+
+    let error = spawn_error(&mut world, (("size", 1.0_f32), ("size", 2.0_f32)));
+    assert!(matches!(
+        error,
+        BbEcsError::DuplicateComponent(name) if name == "size"
+    ));
+
+    let error = spawn_error(&mut world, ((ENTITY_ID, Point::new(0.0, 0.0)),));
+    assert!(matches!(error, BbEcsError::BuiltInComponent(_)));
+
+    assert_eq!(entity_count(&world)?, 1);
+    assert_eq!(world.query_typed::<(&Point,)>(["location"])?.len(), 0);
+    Ok(())
+}
+
+#[test]
+fn bundles_can_be_spawned_through_commands() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("size")?;
+
+    let mut commands = Commands::new();
+    commands.spawn_bundle((("location", Point::new(0.0, 0.0)), ("size", 2.0_f32)));
+    commands.spawn_bundle((("location", Point::new(0.0, 0.0)), ("size", 2_u32)));
+    world.queue_commands(commands);
+
+    assert!(world.update().is_err());
+    assert_eq!(entity_count(&world)?, 1);
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use bbecs::data_types::point::Point;
+use bbecs::errors::Result;
+use bbecs::resources::resource::ResourceCast;
+use bbecs::systems::schedule::{Schedule, UPDATE};
+use bbecs::systems::System;
+use bbecs::world::query::QueryFilter;
+use bbecs::world::{World, WorldMethods};
+
+fn spawn_at(world: &mut World, x: f32) -> Result<()> {
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(x, 0.0))?
+        .with_component("velocity", Point::new(0.0, 0.0))?;
+    Ok(())
+}
+
+fn locations(world: &World, filter: &QueryFilter) -> Result<Vec<Point>> {
+    Ok(world
+        .query_typed_filtered::<(&Point,)>(["location"], filter)?
+        .map(|(location,)| *location)
+        .collect())
+}
+
+#[test]
+fn added_filter_only_returns_components_added_since_the_tick() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("velocity")?;
+    spawn_at(&mut world, 1.0)?;
+
+    let last_run = world.change_tick();
+    world.update()?;
+    spawn_at(&mut world, 2.0)?;
+
+    let filter = QueryFilter::new().added("location").since(last_run);
+    assert_eq!(locations(&world, &filter)?, vec![Point::new(2.0, 0.0)]);
+
+    let filter = QueryFilter::new().added("location").since(0);
+    assert_eq!(locations(&world, &filter)?.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn mutating_through_a_typed_query_marks_the_component_as_changed() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("velocity")?;
+    spawn_at(&mut world, 1.0)?;
+    spawn_at(&mut world, 2.0)?;
+
+    let last_run = world.change_tick();
+    world.update()?;
+
+    for (mut location,) in world.query_typed::<(&mut Point,)>(["location"])? {
+        if location.x > 1.5 {
+            location.x += 1.0;
+        }
+    }
+
+    let filter = QueryFilter::new().changed("location").since(last_run);
+    assert_eq!(locations(&world, &filter)?, vec![Point::new(3.0, 0.0)]);
+    let filter = QueryFilter::new().added("location").since(last_run);
+    assert!(locations(&world, &filter)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn adding_another_component_does_not_change_existing_ones() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("velocity")?;
+    world.register("frozen")?;
+    let entity = world
+        .spawn_entity()?
+        .with_component("location", Point::new(1.0, 0.0))?
+        .entity();
+
+    let last_run = world.change_tick();
+    world.update()?;
+    world.insert_component(entity, "frozen", true)?;
+
+    let filter = QueryFilter::new().changed("location").since(last_run);
+    assert!(locations(&world, &filter)?.is_empty());
+    let filter = QueryFilter::new().added("frozen").since(last_run);
+    assert_eq!(locations(&world, &filter)?, vec![Point::new(1.0, 0.0)]);
+    Ok(())
+}
+
+struct CountMoved {
+    last_run: u32,
+}
+
+impl System for CountMoved {
+    fn run(&mut self, world: &mut World) -> Result<()> {
+        let filter = QueryFilter::new().changed("location").since(self.last_run);
+        self.last_run = world.change_tick();
+        let moved = world
+            .query_typed_filtered::<(&Point,)>(["location"], &filter)?
+            .len();
+        world.add_resource("moved".to_owned(), moved);
+        Ok(())
+    }
+}
+
+fn moved(world: &World) -> Result<usize> {
+    let wrapper = world.get_resource("moved")?.borrow();
+    let moved: &usize = wrapper.cast()?;
+    Ok(*moved)
+}
+
+#[test]
+fn systems_see_changes_made_since_they_last_ran() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("velocity")?;
+    spawn_at(&mut world, 1.0)?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(2.0, 0.0))?
+        .with_component("velocity", Point::new(1.0, 0.0))?;
+
+    let mut schedule = Schedule::new();
+    schedule
+        .add_system(UPDATE, CountMoved { last_run: 0 })?
+        .add_system(UPDATE, |world: &mut World| {
+            for (mut location, velocity) in
+                world.query_typed::<(&mut Point, &Point)>(["location", "velocity"])?
+            {
+                if velocity.x != 0.0 {
+                    location.x += velocity.x;
+                }
+            }
+            Ok(())
+        })?;
+
+    schedule.run(&mut world)?;
+    assert_eq!(moved(&world)?, 2);
+    schedule.run(&mut world)?;
+    assert_eq!(moved(&world)?, 1);
+    Ok(())
+}
+
+#[test]
+fn filtering_on_an_unregistered_component_is_an_error() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+
+    let filter = QueryFilter::new().changed("speed");
+    assert!(world
+        .query_typed_filtered::<(&Point,)>(["location"], &filter)
+        .is_err());
+    Ok(())
+}
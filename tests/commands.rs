@@ -0,0 +1,106 @@
+use bbecs::data_types::point::Point;
+use bbecs::errors::{BbEcsError, Result};
+use bbecs::world::commands::Commands;
+use bbecs::world::World;
+
+#[test]
+fn commands_recorded_during_a_query_are_applied_on_update() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("on fire")?;
+
+    let burning = world
+        .spawn_entity()?
+        .with_component("location", Point::new(0.0, 0.0))?
+        .with_component("on fire", true)?
+        .entity();
+    let doomed = world
+        .spawn_entity()?
+        .with_component("location", Point::new(5.0, 5.0))?
+        .entity();
+
+    let mut commands = Commands::new();
+    for (location,) in world.query_typed::<(&Point,)>(["location"])? {
+        commands
+            .spawn()
+            .with_component("location", *location)
+            .with_component("on fire", false);
+    }
+    commands.remove_component::<bool>(burning, "on fire");
+    commands.despawn(doomed);
+    world.queue_commands(commands);
+
+    assert_eq!(world.query_typed::<(&Point,)>(["location"])?.len(), 2);
+
+    world.update()?;
+
+    assert_eq!(world.query_typed::<(&Point,)>(["location"])?.len(), 3);
+    assert_eq!(world.query_typed::<(&bool,)>(["on fire"])?.len(), 2);
+    assert!(!world.has_component(burning, "on fire")?);
+    assert!(!world.is_alive(doomed));
+    Ok(())
+}
+
+#[test]
+fn failed_commands_are_reported_individually() -> Result<()> {
+    let mut world = World::new();
+    world.register("size")?;
+    let entity = world.spawn_entity()?.entity();
+
+    let mut commands = Commands::new();
+    commands.insert_component(entity, "size", 1_u32);
+    commands.insert_component(entity, "not registered", 1_u32);
+    commands
+        .spawn()
+        .with_component("size", 2_u32)
+        .with_component("size", 2.0_f32);
+    commands.spawn().with_component("size", 3_u32);
+
+    let errors = world.apply_commands(commands);
+    let failed: Vec<usize> = errors.iter().map(|error| error.index).collect();
+    let sizes: Vec<u32> = world
+        .query_typed::<(&u32,)>(["size"])?
+        .map(|(size,)| *size)
+        .collect();
+
+    assert_eq!(failed, vec![1, 2]);
+    assert_eq!(sizes, vec![1, 3]);
+    Ok(())
+}
+
+#[test]
+fn update_returns_the_errors_from_queued_commands() -> Result<()> {
+    let mut world = World::new();
+    let entity = world.spawn_entity()?.entity();
+    world.despawn(entity)?;
+
+    let mut commands = Commands::new();
+    commands.despawn(entity);
+    world.queue_commands(commands);
+
+    let error = world.update().unwrap_err();
+    match error {
+        BbEcsError::CommandsFailed(errors) => assert_eq!(errors.len(), 1),
+        _ => panic!("expected the queued command to fail"),
+    }
+    Ok(())
+}
+
+#[cfg(feature = "eyre")]
+#[test]
+fn commands_can_fail_with_eyre_errors() -> Result<()> {
+    let mut world = World::new();
+
+    let mut commands = Commands::new();
+    commands.add(|_| Err(eyre::eyre!("out of ammo").into()));
+    world.queue_commands(commands);
+
+    match world.update().unwrap_err() {
+        BbEcsError::CommandsFailed(errors) => {
+            assert!(matches!(errors[0].error, BbEcsError::Other(_)));
+            assert_eq!(errors[0].error.to_string(), "out of ammo");
+        }
+        error => panic!("expected the queued command to fail but got {}", error),
+    }
+    Ok(())
+}
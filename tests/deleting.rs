@@ -1,10 +1,7 @@
-use std::cell::RefCell;
-use std::rc::Rc;
-
 use bbecs::components::CastComponents;
 use bbecs::data_types::point::Point;
-use bbecs::world::{DataWrapper, World, WorldMethods, ENTITY_ID};
-use eyre::Result;
+use bbecs::errors::Result;
+use bbecs::world::{DataWrapper, DeletionStrategy, Entity, World, ENTITY_ID};
 
 #[test]
 fn deleting_an_entity_by_id() -> Result<()> {
@@ -32,10 +29,10 @@ fn deleting_an_entity_by_id() -> Result<()> {
         let ids = query_results.get(ENTITY_ID).unwrap();
 
         for (index, location) in locations.iter().enumerate() {
-            let wrapped_location: &Rc<RefCell<Point>> = location.cast()?;
+            let wrapped_location: &DataWrapper<Point> = location.cast()?;
             let location = wrapped_location.borrow();
             if *location == Point::new(10.0, 10.0) {
-                let wrapped_id: &Rc<RefCell<u32>> = ids[index].cast()?;
+                let wrapped_id: &DataWrapper<Entity> = ids[index].cast()?;
                 let id = wrapped_id.borrow();
                 world.delete_by_id(*id)?;
             }
@@ -46,7 +43,7 @@ fn deleting_an_entity_by_id() -> Result<()> {
 
     let query_results = world.query(vec!["location"])?;
     let queried_locations = query_results.get("location").unwrap();
-    let wrapped_location: &Rc<RefCell<Point>> = queried_locations[1].cast()?;
+    let wrapped_location: &DataWrapper<Point> = queried_locations[1].cast()?;
     let location = wrapped_location.borrow();
 
     assert_eq!(queried_locations.len(), 2);
@@ -64,7 +61,7 @@ fn inserting_an_entity_after_deleting_should_work() -> Result<()> {
 
     let query = world.query(vec!["size", ENTITY_ID])?;
     let _wrapped_size: &DataWrapper<f32> = query.get("size").unwrap()[0].cast()?;
-    let wrapped_id: &DataWrapper<u32> = query.get(ENTITY_ID).unwrap()[0].cast()?;
+    let wrapped_id: &DataWrapper<Entity> = query.get(ENTITY_ID).unwrap()[0].cast()?;
 
     let id = *wrapped_id.borrow();
 
@@ -72,14 +69,189 @@ fn inserting_an_entity_after_deleting_should_work() -> Result<()> {
 
     world.update()?;
 
-    world.spawn_entity()?.with_component("size", 30.0_f32)?;
+    let new_id = world
+        .spawn_entity()?
+        .with_component("size", 30.0_f32)?
+        .entity();
 
     let query = world.query(vec!["size", ENTITY_ID])?;
     let wrapped_size: &DataWrapper<f32> = query.get("size").unwrap()[0].cast()?;
-    let wrapped_id: &DataWrapper<u32> = query.get(ENTITY_ID).unwrap()[0].cast()?;
+    let wrapped_id: &DataWrapper<Entity> = query.get(ENTITY_ID).unwrap()[0].cast()?;
 
-    assert_eq!(1, *wrapped_id.borrow());
+    assert_eq!(new_id, *wrapped_id.borrow());
+    assert_ne!(id, new_id);
     assert_eq!(*wrapped_size.borrow(), 30.0_f32);
 
     Ok(())
 }
+
+#[test]
+fn deleted_entity_handles_are_stale_after_their_index_is_reused() -> Result<()> {
+    let mut world = World::new();
+    let first = world.spawn_entity()?.entity();
+    let second = world.spawn_entity()?.entity();
+
+    world.delete_by_id(first)?;
+    world.update()?;
+
+    let recycled = world.spawn_entity()?.entity();
+
+    assert_eq!(recycled.index(), first.index());
+    assert!(!world.is_alive(first));
+    assert!(world.is_alive(second));
+    assert!(world.is_alive(recycled));
+    assert!(world.delete_by_id(first).is_err());
+    Ok(())
+}
+
+#[test]
+fn deleting_by_handle_after_other_entities_are_removed() -> Result<()> {
+    let mut world = World::new();
+    world.register("size")?;
+    let first = world
+        .spawn_entity()?
+        .with_component("size", 1_u32)?
+        .entity();
+    let second = world
+        .spawn_entity()?
+        .with_component("size", 2_u32)?
+        .entity();
+    let third = world
+        .spawn_entity()?
+        .with_component("size", 3_u32)?
+        .entity();
+
+    world.delete_by_id(first)?;
+    world.update()?;
+    world.delete_by_id(third)?;
+    world.update()?;
+
+    let sizes: Vec<u32> = world
+        .query_typed::<(&u32,)>(["size"])?
+        .map(|(size,)| *size)
+        .collect();
+
+    assert_eq!(sizes, vec![2]);
+    assert!(world.is_alive(second));
+    Ok(())
+}
+
+#[test]
+fn deleting_many_entities_in_one_update_keeps_the_order() -> Result<()> {
+    let mut world = World::new();
+    world.register("size")?;
+
+    for size in 0..100_u32 {
+        let entity = world.spawn_entity()?.with_component("size", size)?.entity();
+        if size % 3 == 0 {
+            world.delete_by_id(entity)?;
+        }
+    }
+
+    world.update()?;
+
+    let sizes: Vec<u32> = world
+        .query_typed::<(&u32,)>(["size"])?
+        .map(|(size,)| *size)
+        .collect();
+    let expected: Vec<u32> = (0..100).filter(|size| size % 3 != 0).collect();
+
+    assert_eq!(sizes, expected);
+    Ok(())
+}
+
+#[test]
+fn deleting_with_swap_remove_keeps_every_other_entity() -> Result<()> {
+    let mut world = World::new();
+    world.register("size")?;
+    world.set_deletion_strategy(DeletionStrategy::SwapRemove);
+
+    let mut kept = vec![];
+    for size in 0..20_u32 {
+        let entity = world.spawn_entity()?.with_component("size", size)?.entity();
+        if size % 4 == 0 {
+            world.delete_by_id(entity)?;
+        } else {
+            kept.push((entity, size));
+        }
+    }
+
+    world.update()?;
+
+    let mut sizes: Vec<u32> = world
+        .query_typed::<(&u32,)>(["size"])?
+        .map(|(size,)| *size)
+        .collect();
+    sizes.sort_unstable();
+
+    assert_eq!(
+        sizes,
+        kept.iter().map(|(_, size)| *size).collect::<Vec<_>>()
+    );
+    for (entity, size) in kept {
+        world.delete_by_id(entity)?;
+        world.update()?;
+        let remaining = world.query_typed::<(&u32,)>(["size"])?;
+        assert!(remaining
+            .map(|(remaining,)| *remaining)
+            .all(|remaining| remaining != size));
+    }
+    Ok(())
+}
+
+#[test]
+fn despawning_removes_the_entity_immediately() -> Result<()> {
+    let mut world = World::new();
+    world.register("size")?;
+    let first = world
+        .spawn_entity()?
+        .with_component("size", 1_u32)?
+        .entity();
+    let second = world
+        .spawn_entity()?
+        .with_component("size", 2_u32)?
+        .entity();
+    let third = world
+        .spawn_entity()?
+        .with_component("size", 3_u32)?
+        .entity();
+
+    world.despawn(second)?;
+
+    let sizes: Vec<u32> = world
+        .query_typed::<(&u32,)>(["size"])?
+        .map(|(size,)| *size)
+        .collect();
+
+    assert_eq!(sizes, vec![1, 3]);
+    assert!(!world.is_alive(second));
+    assert!(world.despawn(second).is_err());
+    world.insert_component(third, "size", 30_u32)?;
+    world.insert_component(first, "size", 10_u32)?;
+
+    let sizes: Vec<u32> = world
+        .query_typed::<(&u32,)>(["size"])?
+        .map(|(size,)| *size)
+        .collect();
+    assert_eq!(sizes, vec![10, 30]);
+    Ok(())
+}
+
+#[test]
+fn entities_flagged_for_deletion_are_skipped_by_queries() -> Result<()> {
+    let mut world = World::new();
+    world.register("size")?;
+    world.spawn_entity()?.with_component("size", 1_u32)?;
+    let flagged = world
+        .spawn_entity()?
+        .with_component("size", 2_u32)?
+        .entity();
+
+    world.delete_by_id(flagged)?;
+
+    let query_results = world.query(vec!["size"])?;
+    assert_eq!(query_results.get("size").unwrap().len(), 1);
+    assert_eq!(world.query_typed::<(&u32,)>(["size"])?.len(), 1);
+    assert!(world.is_alive(flagged));
+    Ok(())
+}
@@ -0,0 +1,99 @@
+#![cfg(feature = "derive")]
+
+use bbecs::components::Component;
+use bbecs::data_types::point::Point;
+use bbecs::errors::Result;
+use bbecs::resources::resource::ResourceCast;
+use bbecs::resources::{NamedResource, Resource};
+use bbecs::world::bundle::Bundle;
+use bbecs::world::{Entity, World, ENTITY_ID};
+
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+struct GunCooldown(f32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+struct UIScale(f32);
+
+#[derive(Debug, Clone, PartialEq, Component)]
+#[component(name = "name tag")]
+struct Name(String);
+
+#[derive(Debug, PartialEq, Resource)]
+struct HighScore(u32);
+
+#[derive(Clone, Bundle)]
+struct Ship {
+    location: Point,
+    #[bundle(name = "speed")]
+    velocity: Point,
+    gun_cooldown: GunCooldown,
+}
+
+#[test]
+fn components_are_named_after_their_type() {
+    assert_eq!(GunCooldown::NAME, "gun cooldown");
+    assert_eq!(UIScale::NAME, "ui scale");
+    assert_eq!(Name::NAME, "name tag");
+    assert_eq!(HighScore::NAME, "high score");
+}
+
+#[test]
+fn derived_components_are_registered_and_inserted_by_type() -> Result<()> {
+    let mut world = World::new();
+    world.register_component::<GunCooldown>()?;
+    world.register_component::<Name>()?;
+
+    let entity = world
+        .spawn_entity()?
+        .with(GunCooldown(0.5))?
+        .with(Name("player".to_owned()))?
+        .entity();
+
+    let (id, cooldown, name) = world
+        .query_typed::<(&Entity, &GunCooldown, &Name)>([ENTITY_ID, GunCooldown::NAME, Name::NAME])?
+        .next()
+        .unwrap();
+    assert_eq!(*id, entity);
+    assert_eq!(*cooldown, GunCooldown(0.5));
+    assert_eq!(name.0, "player");
+    Ok(())
+}
+
+#[test]
+fn derived_bundles_store_each_field_as_a_component() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("speed")?;
+    world.register_component::<GunCooldown>()?;
+
+    let ship = Ship {
+        location: Point::new(1.0, 1.0),
+        velocity: Point::new(0.0, 2.0),
+        gun_cooldown: GunCooldown(1.0),
+    };
+    let names: Vec<String> = ship
+        .clone()
+        .into_components()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(names, vec!["location", "speed", "gun cooldown"]);
+
+    let entity = world.spawn_bundle(ship)?.entity();
+
+    assert!(world.has_component(entity, "speed")?);
+    assert!(world.has_component(entity, GunCooldown::NAME)?);
+    Ok(())
+}
+
+#[test]
+fn derived_resources_are_added_by_type() -> Result<()> {
+    let mut world = World::new();
+
+    world.add_named_resource(HighScore(100));
+
+    let wrapper = world.get_resource(HighScore::NAME)?.borrow();
+    let score: &HighScore = wrapper.cast()?;
+    assert_eq!(*score, HighScore(100));
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use bbecs::errors::{BbEcsError, Result};
+use bbecs::resources::events::{EventReader, Events};
+use bbecs::resources::resource::ResourceCast;
+use bbecs::world::World;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Sound {
+    Bounce,
+    Score,
+}
+
+fn read_sounds(world: &World, reader: &mut EventReader<Sound>) -> Result<Vec<Sound>> {
+    let wrapper = world.get_resource("sounds")?.borrow();
+    let events: &Events<Sound> = wrapper.cast()?;
+    Ok(reader.read(events).copied().collect())
+}
+
+#[test]
+fn readers_see_each_event_once() -> Result<()> {
+    let mut world = World::new();
+    world.add_events::<Sound>("sounds");
+    let mut reader = EventReader::new();
+
+    world.send_event("sounds", Sound::Bounce)?;
+    world.update()?;
+    world.send_event("sounds", Sound::Score)?;
+
+    assert_eq!(
+        read_sounds(&world, &mut reader)?,
+        vec![Sound::Bounce, Sound::Score]
+    );
+    assert!(read_sounds(&world, &mut reader)?.is_empty());
+
+    world.update()?;
+    assert!(read_sounds(&world, &mut reader)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn readers_that_run_before_the_writer_see_events_on_the_next_update() -> Result<()> {
+    let mut world = World::new();
+    world.add_events::<Sound>("sounds");
+    let mut reader = EventReader::new();
+
+    assert!(read_sounds(&world, &mut reader)?.is_empty());
+    world.send_event("sounds", Sound::Bounce)?;
+    world.update()?;
+
+    assert_eq!(read_sounds(&world, &mut reader)?, vec![Sound::Bounce]);
+    world.send_event("sounds", Sound::Score)?;
+    world.update()?;
+
+    assert_eq!(read_sounds(&world, &mut reader)?, vec![Sound::Score]);
+    Ok(())
+}
+
+#[test]
+fn events_are_dropped_after_two_updates() -> Result<()> {
+    let mut world = World::new();
+    world.add_events::<Sound>("sounds");
+
+    world.send_event("sounds", Sound::Bounce)?;
+    world.update()?;
+    world.update()?;
+
+    let mut late_reader = EventReader::new();
+    assert!(read_sounds(&world, &mut late_reader)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn sending_an_event_of_the_wrong_type_is_an_error() {
+    let mut world = World::new();
+    world.add_events::<Sound>("sounds");
+
+    let error = world.send_event("sounds", 5_u32).unwrap_err();
+    assert!(matches!(
+        error,
+        BbEcsError::CastingResource(Some(name), _, expected)
+            if name == "sounds" && expected == std::any::type_name::<Events<u32>>()
+    ));
+
+    let error = world.send_event("music", Sound::Score).unwrap_err();
+    assert!(matches!(error, BbEcsError::ResourceNotFound(_)));
+}
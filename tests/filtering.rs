@@ -0,0 +1,193 @@
+use bbecs::components::CastComponents;
+use bbecs::data_types::point::Point;
+use bbecs::errors::Result;
+use bbecs::query;
+use bbecs::world::query::QueryFilter;
+use bbecs::world::{DataWrapper, World};
+
+#[test]
+fn without_filter_leaves_out_entities_with_the_component() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("velocity")?;
+    world.register("frozen")?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(0.0, 0.0))?
+        .with_component("velocity", Point::new(1.0, 0.0))?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(1.0, 0.0))?
+        .with_component("velocity", Point::new(1.0, 0.0))?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(2.0, 0.0))?
+        .with_component("velocity", Point::new(1.0, 0.0))?
+        .with_component("frozen", true)?;
+    let filter = QueryFilter::new().without("frozen");
+
+    let mut locations: Vec<f32> = world
+        .query_typed_filtered::<(&Point, &Point)>(["location", "velocity"], &filter)?
+        .map(|(location, _)| location.x)
+        .collect();
+    locations.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    assert_eq!(locations, vec![0.0, 1.0]);
+    Ok(())
+}
+
+#[test]
+fn with_filter_requires_the_component_without_fetching_it() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("velocity")?;
+    world.register("size")?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(0.0, 0.0))?
+        .with_component("velocity", Point::new(1.0, 0.0))?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(1.0, 0.0))?
+        .with_component("velocity", Point::new(1.0, 0.0))?
+        .with_component("size", 5.0_f32)?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(3.0, 0.0))?
+        .with_component("size", 10.0_f32)?;
+    let filter = QueryFilter::new().with("size").with("velocity");
+
+    let locations: Vec<Point> = world
+        .query_typed_filtered::<(&Point,)>(["location"], &filter)?
+        .map(|(location,)| *location)
+        .collect();
+
+    assert_eq!(locations, vec![Point::new(1.0, 0.0)]);
+    Ok(())
+}
+
+#[test]
+fn optional_components_are_none_for_entities_without_them() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("size")?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(0.0, 0.0))?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(1.0, 0.0))?
+        .with_component("size", 5.0_f32)?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(2.0, 0.0))?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(3.0, 0.0))?
+        .with_component("size", 10.0_f32)?;
+
+    let mut results: Vec<(f32, Option<f32>)> = world
+        .query_typed::<(&Point, Option<&f32>)>(["location", "size"])?
+        .map(|(location, size)| (location.x, size.map(|size| *size)))
+        .collect();
+    results.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    assert_eq!(
+        results,
+        vec![
+            (0.0, None),
+            (1.0, Some(5.0)),
+            (2.0, None),
+            (3.0, Some(10.0))
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn optional_components_can_be_changed() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("size")?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(0.0, 0.0))?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(1.0, 0.0))?
+        .with_component("size", 5.0_f32)?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(3.0, 0.0))?
+        .with_component("size", 10.0_f32)?;
+
+    for (_, size) in world.query_typed::<(&Point, Option<&mut f32>)>(["location", "size"])? {
+        if let Some(mut size) = size {
+            *size *= 2.0;
+        }
+    }
+
+    let mut sizes: Vec<f32> = world
+        .query_typed::<(&f32,)>(["size"])?
+        .map(|(size,)| *size)
+        .collect();
+    sizes.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(sizes, vec![10.0, 20.0]);
+    Ok(())
+}
+
+#[test]
+fn query_macro_supports_optional_and_excluded_components() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("velocity")?;
+    world.register("size")?;
+    world.register("frozen")?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(0.0, 0.0))?
+        .with_component("velocity", Point::new(1.0, 0.0))?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(1.0, 0.0))?
+        .with_component("velocity", Point::new(1.0, 0.0))?
+        .with_component("size", 5.0_f32)?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(2.0, 0.0))?
+        .with_component("velocity", Point::new(1.0, 0.0))?
+        .with_component("frozen", true)?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(3.0, 0.0))?
+        .with_component("size", 10.0_f32)?;
+
+    let query;
+    let (locations, sizes) =
+        query!(world, query, "location"; optional "size"; with "velocity"; without "frozen");
+
+    assert_eq!(locations.len(), 2);
+    assert_eq!(sizes.len(), 2);
+    for (index, location) in locations.iter().enumerate() {
+        let location: &DataWrapper<Point> = location.cast()?;
+        let has_size = location.borrow().x == 1.0;
+        assert_eq!(sizes[index].is_some(), has_size);
+    }
+    Ok(())
+}
+
+#[test]
+fn filtering_on_an_unregistered_component_is_an_error() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(0.0, 0.0))?;
+
+    let filter = QueryFilter::new().without("asleep");
+    assert!(world.query_filtered(vec!["location"], &filter).is_err());
+    assert!(world
+        .query_typed::<(&Point, Option<&f32>)>(["location", "speed"])
+        .is_err());
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use bbecs::errors::{BbEcsError, Result};
+use bbecs::world::commands::Commands;
+use bbecs::world::{Entity, World};
+
+struct Ship {
+    hull: Entity,
+    turret: Entity,
+    barrel: Entity,
+    engine: Entity,
+}
+
+fn build_ship(world: &mut World) -> Result<Ship> {
+    let hull = world.spawn_entity()?.entity();
+    let turret = world.spawn_entity()?.with_parent(hull)?.entity();
+    let barrel = world.spawn_entity()?.with_parent(turret)?.entity();
+    let engine = world.spawn_entity()?.with_parent(hull)?.entity();
+
+    Ok(Ship {
+        hull,
+        turret,
+        barrel,
+        engine,
+    })
+}
+
+#[test]
+fn children_and_ancestors_can_be_iterated() -> Result<()> {
+    let mut world = World::new();
+    let ship = build_ship(&mut world)?;
+
+    assert_eq!(world.children(ship.hull), &[ship.turret, ship.engine]);
+    assert_eq!(world.parent(ship.barrel), Some(ship.turret));
+    assert_eq!(
+        world.ancestors(ship.barrel).collect::<Vec<_>>(),
+        vec![ship.turret, ship.hull]
+    );
+    assert_eq!(
+        world.descendants(ship.hull),
+        vec![ship.turret, ship.barrel, ship.engine]
+    );
+    assert_eq!(world.ancestors(ship.hull).count(), 0);
+    Ok(())
+}
+
+#[test]
+fn changing_parent_moves_the_child() -> Result<()> {
+    let mut world = World::new();
+    let ship = build_ship(&mut world)?;
+
+    world.set_parent(ship.barrel, ship.engine)?;
+    assert!(world.children(ship.turret).is_empty());
+    assert_eq!(world.children(ship.engine), &[ship.barrel]);
+
+    assert_eq!(world.remove_parent(ship.barrel)?, Some(ship.engine));
+    assert_eq!(world.parent(ship.barrel), None);
+    Ok(())
+}
+
+#[test]
+fn entities_cannot_become_their_own_ancestor() -> Result<()> {
+    let mut world = World::new();
+    let ship = build_ship(&mut world)?;
+
+    for (child, parent) in [(ship.hull, ship.barrel), (ship.turret, ship.turret)] {
+        let error = world.set_parent(child, parent).unwrap_err();
+        assert!(matches!(error, BbEcsError::HierarchyCycle(_, _)));
+    }
+    assert_eq!(world.parent(ship.hull), None);
+    Ok(())
+}
+
+#[test]
+fn despawning_a_parent_leaves_the_children_without_a_parent() -> Result<()> {
+    let mut world = World::new();
+    let ship = build_ship(&mut world)?;
+
+    world.despawn(ship.hull)?;
+
+    assert!(world.is_alive(ship.turret));
+    assert_eq!(world.parent(ship.turret), None);
+    assert_eq!(world.parent(ship.engine), None);
+    assert_eq!(world.children(ship.turret), &[ship.barrel]);
+    Ok(())
+}
+
+#[test]
+fn despawning_recursively_removes_every_descendant() -> Result<()> {
+    let mut world = World::new();
+    let ship = build_ship(&mut world)?;
+    let other = world.spawn_entity()?.entity();
+
+    world.despawn_recursive(ship.turret)?;
+
+    assert!(!world.is_alive(ship.turret));
+    assert!(!world.is_alive(ship.barrel));
+    assert!(world.is_alive(ship.engine));
+    assert!(world.is_alive(other));
+    assert_eq!(world.children(ship.hull), &[ship.engine]);
+    Ok(())
+}
+
+#[test]
+fn deleted_entities_are_removed_from_the_hierarchy_on_update() -> Result<()> {
+    let mut world = World::new();
+    let ship = build_ship(&mut world)?;
+
+    world.delete_by_id(ship.engine)?;
+    world.delete_recursive_by_id(ship.turret)?;
+    assert_eq!(world.children(ship.hull).len(), 2);
+
+    world.update()?;
+
+    assert!(!world.is_alive(ship.barrel));
+    assert!(world.children(ship.hull).is_empty());
+    Ok(())
+}
+
+#[test]
+fn parents_can_be_set_through_commands() -> Result<()> {
+    let mut world = World::new();
+    let hull = world.spawn_entity()?.entity();
+
+    let mut commands = Commands::new();
+    commands.spawn().with_parent(hull);
+    commands.spawn().with_parent(hull);
+    world.queue_commands(commands);
+    world.update()?;
+    assert_eq!(world.children(hull).len(), 2);
+
+    let mut commands = Commands::new();
+    commands.despawn_recursive(hull);
+    world.queue_commands(commands);
+    world.update()?;
+    assert!(!world.is_alive(hull));
+    Ok(())
+}
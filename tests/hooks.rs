@@ -0,0 +1,126 @@
+use bbecs::data_types::point::Point;
+use bbecs::errors::Result;
+use bbecs::resources::resource::ResourceCast;
+use bbecs::world::commands::Commands;
+use bbecs::world::{Entity, World, WorldMethods};
+
+fn log(event: &'static str) -> impl Fn(Entity, &mut Commands) {
+    move |entity, commands| {
+        commands.add(move |world| {
+            let mut wrapper = world.get_resource("log")?.borrow_mut();
+            let log: &mut Vec<String> = wrapper.cast_mut()?;
+            log.push(format!("{} {}", event, entity));
+            Ok(())
+        });
+    }
+}
+
+fn logged(world: &World) -> Result<Vec<String>> {
+    let wrapper = world.get_resource("log")?.borrow();
+    let log: &Vec<String> = wrapper.cast()?;
+    Ok(log.clone())
+}
+
+#[test]
+fn insert_and_remove_hooks_run_for_their_component() -> Result<()> {
+    let mut world = World::new();
+    world.register("sound")?;
+    world.register("location")?;
+    world.add_resource("log".to_owned(), Vec::<String>::new());
+    world.on_insert("sound", log("inserted"));
+    world.on_remove("sound", log("removed"));
+
+    let entity = world
+        .spawn_entity()?
+        .with_component("sound", 1_u32)?
+        .with_component("location", Point::new(0.0, 0.0))?
+        .entity();
+    world.insert_component(entity, "sound", 2_u32)?;
+    world.remove_component::<u32>(entity, "sound")?;
+    world.update()?;
+
+    assert_eq!(
+        logged(&world)?,
+        vec![
+            format!("inserted {}", entity),
+            format!("inserted {}", entity),
+            format!("removed {}", entity),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn despawn_hooks_run_when_flagged_entities_are_deleted() -> Result<()> {
+    let mut world = World::new();
+    world.register("sound")?;
+    world.register("location")?;
+    world.register("explosion")?;
+    world.on_despawn("sound", |entity, commands| {
+        commands
+            .spawn()
+            .with_component("explosion", entity)
+            .with_component("location", Point::new(0.0, 0.0));
+    });
+
+    let loud = world
+        .spawn_entity()?
+        .with_component("sound", 1_u32)?
+        .entity();
+    let quiet = world
+        .spawn_entity()?
+        .with_component("location", Point::new(0.0, 0.0))?
+        .entity();
+    world.delete_by_id(loud)?;
+    world.delete_by_id(quiet)?;
+
+    world.update()?;
+    assert!(!world.is_alive(loud));
+    assert_eq!(world.query_typed::<(&Entity,)>(["explosion"])?.len(), 0);
+
+    world.update()?;
+    let explosions: Vec<Entity> = world
+        .query_typed::<(&Entity,)>(["explosion"])?
+        .map(|(entity,)| *entity)
+        .collect();
+    assert_eq!(explosions, vec![loud]);
+    Ok(())
+}
+
+#[test]
+fn despawn_hooks_run_when_entities_are_despawned() -> Result<()> {
+    let mut world = World::new();
+    world.register("sound")?;
+    world.register("location")?;
+    world.add_resource("log".to_owned(), Vec::<String>::new());
+    world.on_despawn("sound", log("despawned"));
+    world.on_despawn("location", log("despawned"));
+
+    let entity = world
+        .spawn_entity()?
+        .with_component("sound", 1_u32)?
+        .with_component("location", Point::new(0.0, 0.0))?
+        .entity();
+    world.despawn(entity)?;
+    world.update()?;
+
+    assert_eq!(logged(&world)?.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn hooks_run_for_components_spawned_in_a_bundle() -> Result<()> {
+    let mut world = World::new();
+    world.register("sound")?;
+    world.register("location")?;
+    world.add_resource("log".to_owned(), Vec::<String>::new());
+    world.on_insert("location", log("inserted"));
+
+    let entity = world
+        .spawn_bundle((("sound", 1_u32), ("location", Point::new(0.0, 0.0))))?
+        .entity();
+    world.update()?;
+
+    assert_eq!(logged(&world)?, vec![format!("inserted {}", entity)]);
+    Ok(())
+}
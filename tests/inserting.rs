@@ -0,0 +1,97 @@
+use bbecs::data_types::point::Point;
+use bbecs::errors::{BbEcsError, Result};
+use bbecs::world::{World, ENTITY_ID};
+
+#[test]
+fn inserting_a_component_onto_an_earlier_entity() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("stunned")?;
+
+    let first = world
+        .spawn_entity()?
+        .with_component("location", Point::new(0.0, 0.0))?
+        .entity();
+    let second = world
+        .spawn_entity()?
+        .with_component("location", Point::new(10.0, 10.0))?
+        .with_component("stunned", false)?
+        .entity();
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(20.0, 20.0))?;
+
+    world.insert_component(first, "stunned", true)?;
+
+    let stunned: Vec<(Point, bool)> = world
+        .query_typed::<(&Point, &bool)>(["location", "stunned"])?
+        .map(|(location, stunned)| (*location, *stunned))
+        .collect();
+
+    assert_eq!(stunned.len(), 2);
+    assert!(stunned.contains(&(Point::new(0.0, 0.0), true)));
+    assert!(stunned.contains(&(Point::new(10.0, 10.0), false)));
+    assert!(world.has_component(first, "stunned")?);
+    assert!(world.has_component(second, "stunned")?);
+    Ok(())
+}
+
+#[test]
+fn inserting_a_component_the_entity_already_has_replaces_it() -> Result<()> {
+    let mut world = World::new();
+    world.register("health")?;
+
+    let entity = world
+        .spawn_entity()?
+        .with_component("health", 10_u32)?
+        .entity();
+    world.spawn_entity()?.with_component("health", 20_u32)?;
+
+    world.insert_component(entity, "health", 5_u32)?;
+
+    let health: Vec<u32> = world
+        .query_typed::<(&u32,)>(["health"])?
+        .map(|(health,)| *health)
+        .collect();
+
+    assert_eq!(health, vec![5, 20]);
+    Ok(())
+}
+
+#[test]
+fn inserting_onto_a_deleted_entity_is_an_error() -> Result<()> {
+    let mut world = World::new();
+    world.register("stunned")?;
+
+    let entity = world.spawn_entity()?.entity();
+    world.delete_by_id(entity)?;
+    world.update()?;
+
+    assert!(world.insert_component(entity, "stunned", true).is_err());
+    Ok(())
+}
+
+#[test]
+fn inserting_an_unregistered_component_is_an_error() -> Result<()> {
+    let mut world = World::new();
+    let entity = world.spawn_entity()?.entity();
+
+    assert!(world.insert_component(entity, "stunned", true).is_err());
+    Ok(())
+}
+
+#[test]
+fn inserting_a_built_in_component_is_an_error() -> Result<()> {
+    let mut world = World::new();
+    let entity = world.spawn_entity()?.entity();
+
+    for name in ["to be deleted", ENTITY_ID] {
+        let error = world.insert_component(entity, name, 5_u32).unwrap_err();
+        assert!(matches!(error, BbEcsError::BuiltInComponent(found) if found == name));
+    }
+
+    world.delete_by_id(entity)?;
+    world.update()?;
+    assert!(!world.is_alive(entity));
+    Ok(())
+}
@@ -1,10 +1,7 @@
-use std::cell::RefCell;
-use std::rc::Rc;
-
 use bbecs::components::CastComponents;
+use bbecs::errors::Result;
 use bbecs::resources::resource::ResourceCast;
-use bbecs::world::{World, WorldMethods};
-use eyre::Result;
+use bbecs::world::{DataWrapper, World, WorldMethods};
 use ggez::graphics::Color;
 
 const RESOURCE_NAME: &str = "color";
@@ -59,7 +56,7 @@ fn integration_test_point_query() -> Result<()> {
     let query_results = world.query(vec!["color"])?;
     let colors = query_results.get("color").unwrap();
 
-    let wrapped_colors: &Rc<RefCell<Color>> = colors[0].cast()?;
+    let wrapped_colors: &DataWrapper<Color> = colors[0].cast()?;
     let color = wrapped_colors.borrow();
 
     assert_eq!(*color, Color::new(0.5, 0.2, 0.8, 1.0));
@@ -79,14 +76,14 @@ fn integration_test_point_query_mut() -> Result<()> {
     {
         let query_results = world.query(vec!["color"])?;
         let colors = query_results.get("color").unwrap();
-        let wrapped_colors: &Rc<RefCell<Color>> = colors[0].cast()?;
+        let wrapped_colors: &DataWrapper<Color> = colors[0].cast()?;
         let mut color = wrapped_colors.borrow_mut();
         color.r += 0.1;
     }
 
     let query_results = world.query(vec!["color"])?;
     let colors = query_results.get("color").unwrap();
-    let wrapped_colors: &Rc<RefCell<Color>> = colors[0].cast()?;
+    let wrapped_colors: &DataWrapper<Color> = colors[0].cast()?;
     let color = wrapped_colors.borrow();
 
     assert_eq!(*color, Color::new(0.6, 0.2, 0.8, 1.0));
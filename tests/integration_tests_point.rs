@@ -1,11 +1,8 @@
-use std::cell::RefCell;
-use std::rc::Rc;
-
 use bbecs::components::CastComponents;
 use bbecs::data_types::point::Point;
+use bbecs::errors::Result;
 use bbecs::resources::resource::ResourceCast;
-use bbecs::world::{World, WorldMethods};
-use eyre::Result;
+use bbecs::world::{DataWrapper, World, WorldMethods};
 
 // insert and get out resources
 // insert and query for components
@@ -65,7 +62,7 @@ fn integration_test_point_query() -> Result<()> {
     let query_results = world.query(vec!["location"])?;
     let locations = query_results.get("location").unwrap();
 
-    let wrapped_location: &Rc<RefCell<Point>> = locations[0].cast()?;
+    let wrapped_location: &DataWrapper<Point> = locations[0].cast()?;
     let location = wrapped_location.borrow();
 
     assert_eq!(*location, Point::new(0.0, 0.0));
@@ -85,14 +82,14 @@ fn integration_test_point_query_mut() -> Result<()> {
     {
         let query_results = world.query(vec!["location"])?;
         let locations = query_results.get("location").unwrap();
-        let wrapped_location: &Rc<RefCell<Point>> = locations[0].cast()?;
+        let wrapped_location: &DataWrapper<Point> = locations[0].cast()?;
         let mut location = wrapped_location.borrow_mut();
         location.x += 10.0;
     }
 
     let query_results = world.query(vec!["location"])?;
     let locations = query_results.get("location").unwrap();
-    let wrapped_location: &Rc<RefCell<Point>> = locations[0].cast()?;
+    let wrapped_location: &DataWrapper<Point> = locations[0].cast()?;
     let location = wrapped_location.borrow();
 
     assert_eq!(*location, Point::new(10.0, 0.0));
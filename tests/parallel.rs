@@ -0,0 +1,141 @@
+use bbecs::errors::Result;
+use bbecs::resources::resource::ResourceCast;
+use bbecs::systems::access::Access;
+use bbecs::systems::schedule::{Schedule, UPDATE};
+use bbecs::world::commands::Commands;
+use bbecs::world::{World, WorldMethods};
+
+fn record_run(world: &World, name: &'static str) -> Result<()> {
+    let mut wrapper = world.get_resource("runs")?.borrow_mut();
+    let runs: &mut Vec<&'static str> = wrapper.cast_mut()?;
+    runs.push(name);
+    Ok(())
+}
+
+fn runs(world: &World) -> Result<Vec<&'static str>> {
+    let wrapper = world.get_resource("runs")?.borrow();
+    let runs: &Vec<&'static str> = wrapper.cast()?;
+    Ok(runs.clone())
+}
+
+#[test]
+fn conflicting_systems_run_in_the_order_they_were_added() -> Result<()> {
+    let mut world = World::new();
+    world.add_resource("runs".to_owned(), Vec::<&'static str>::new());
+    let mut schedule = Schedule::new();
+
+    for name in ["first", "second", "third"] {
+        schedule.add_parallel_system(
+            UPDATE,
+            (
+                Access::new().write_resource("runs"),
+                move |world: &World, _: &mut Commands| record_run(world, name),
+            ),
+        )?;
+    }
+    schedule.run(&mut world)?;
+
+    assert_eq!(runs(&world)?, vec!["first", "second", "third"]);
+    Ok(())
+}
+
+#[test]
+fn commands_are_applied_before_the_next_conflicting_system_runs() -> Result<()> {
+    let mut world = World::new();
+    world.register("size")?;
+    world.add_resource("sizes seen".to_owned(), 0_usize);
+    let mut schedule = Schedule::new();
+
+    schedule
+        .add_parallel_system(
+            UPDATE,
+            (
+                Access::new().write_component("size"),
+                |_: &World, commands: &mut Commands| {
+                    commands.spawn().with_component("size", 3_u32);
+                    Ok(())
+                },
+            ),
+        )?
+        .add_parallel_system(
+            UPDATE,
+            (
+                Access::new()
+                    .read_component("size")
+                    .write_resource("sizes seen"),
+                |world: &World, _: &mut Commands| {
+                    let sizes = world.query_typed::<(&u32,)>(["size"])?.len();
+                    let mut wrapper = world.get_resource("sizes seen")?.borrow_mut();
+                    let sizes_seen: &mut usize = wrapper.cast_mut()?;
+                    *sizes_seen = sizes;
+                    Ok(())
+                },
+            ),
+        )?;
+    schedule.run(&mut world)?;
+
+    let wrapper = world.get_resource("sizes seen")?.borrow();
+    let sizes_seen: &usize = wrapper.cast()?;
+    assert_eq!(*sizes_seen, 1);
+    Ok(())
+}
+
+#[test]
+fn systems_that_delete_entities_run_on_their_own() {
+    let reading = Access::new().read_component("size");
+    let deleting = Access::new().delete_entities();
+
+    assert!(reading.is_compatible(&Access::new().read_component("size")));
+    assert!(!reading.is_compatible(&deleting));
+    assert!(!deleting.is_compatible(&Access::new()));
+}
+
+#[test]
+fn errors_from_parallel_systems_stop_the_schedule() -> Result<()> {
+    let mut world = World::new();
+    world.add_resource("runs".to_owned(), Vec::<&'static str>::new());
+    let mut schedule = Schedule::new();
+
+    schedule
+        .add_parallel_system(
+            UPDATE,
+            (Access::new(), |world: &World, _: &mut Commands| {
+                world.get_resource("missing").map(|_| ())
+            }),
+        )?
+        .add_system(UPDATE, |world: &mut World| record_run(world, "after"))?;
+
+    assert!(schedule.run(&mut world).is_err());
+    assert!(runs(&world)?.is_empty());
+    Ok(())
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn compatible_systems_run_on_separate_threads() -> Result<()> {
+    use std::sync::{Arc, Mutex};
+
+    fn assert_sync<T: Sync>() {}
+    assert_sync::<World>();
+
+    let mut world = World::new();
+    let threads = Arc::new(Mutex::new(vec![]));
+    let mut schedule = Schedule::new();
+
+    for _ in 0..2 {
+        let threads = threads.clone();
+        schedule.add_parallel_system(
+            UPDATE,
+            (Access::new(), move |_: &World, _: &mut Commands| {
+                threads.lock().unwrap().push(std::thread::current().id());
+                Ok(())
+            }),
+        )?;
+    }
+    schedule.run(&mut world)?;
+
+    let threads = threads.lock().unwrap();
+    assert_eq!(threads.len(), 2);
+    assert_ne!(threads[0], threads[1]);
+    Ok(())
+}
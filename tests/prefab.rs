@@ -0,0 +1,207 @@
+#![cfg(feature = "serialize")]
+
+use bbecs::data_types::point::Point;
+use bbecs::errors::{BbEcsError, Result};
+use bbecs::world::prefab::{Prefab, PrefabValue, Prefabs};
+use bbecs::world::{Entity, World, ENTITY_ID, GLOBAL_TRANSFORM, LOCAL_TRANSFORM};
+use ggez::graphics::Color;
+
+const RON_PREFABS: &str = r#"
+{
+    "asteroid": {
+        "location": Point((x: 100.0, y: 50.0)),
+        "size": f32(20.0),
+        "points": u32(10),
+        "solid": bool(true),
+        "color": Color((0.5, 0.5, 0.5, 1.0)),
+        "asteroid": Marker("asteroid"),
+    },
+}
+"#;
+
+const TOML_PREFABS: &str = r#"
+[bullet]
+location = { Point = { x = 0.0, y = 0.0 } }
+speed = { f32 = 8.0 }
+"#;
+
+const JSON_PREFABS: &str = r#"
+{
+    "ship": {
+        "location": { "Point": { "x": 400.0, "y": 300.0 } },
+        "color": { "Color": [1.0, 1.0, 1.0, 1.0] }
+    }
+}
+"#;
+
+fn assert_invalid(error: BbEcsError, prefab: &str, field: &str) {
+    match &error {
+        BbEcsError::InvalidPrefab(found_prefab, found_field, _) => {
+            assert_eq!(found_prefab, prefab);
+            assert_eq!(found_field, field);
+        }
+        _ => panic!("expected an invalid prefab error but got {}", error),
+    }
+}
+
+#[test]
+fn prefabs_are_loaded_from_every_format() -> Result<()> {
+    let mut world = World::new();
+    world.add_prefabs(Prefabs::from_ron(RON_PREFABS)?);
+    world.add_prefabs(Prefabs::from_toml(TOML_PREFABS)?);
+    world.add_prefabs(Prefabs::from_json(JSON_PREFABS)?);
+
+    let mut names: Vec<&String> = world.prefabs().names().collect();
+    names.sort();
+    assert_eq!(names, vec!["asteroid", "bullet", "ship"]);
+    assert_eq!(
+        world.prefabs().get("bullet")?.get("speed"),
+        Some(&PrefabValue::F32(8.0))
+    );
+    Ok(())
+}
+
+#[test]
+fn spawning_a_prefab_inserts_each_of_its_components() -> Result<()> {
+    let mut world = World::new();
+    for name in &["location", "size", "points", "solid", "color", "asteroid"] {
+        world.register(name)?;
+    }
+    world.add_prefabs(Prefabs::from_ron(RON_PREFABS)?);
+
+    let asteroid = world.spawn_prefab("asteroid", &[])?;
+
+    let (id, location, size, points, solid, color, marker) = world
+        .query_typed::<(&Entity, &Point, &f32, &u32, &bool, &Color, &String)>([
+            ENTITY_ID, "location", "size", "points", "solid", "color", "asteroid",
+        ])?
+        .next()
+        .unwrap();
+    assert_eq!(*id, asteroid);
+    assert_eq!(*location, Point::new(100.0, 50.0));
+    assert_eq!(*size, 20.0);
+    assert_eq!(*points, 10);
+    assert!(*solid);
+    assert_eq!(*color, Color::new(0.5, 0.5, 0.5, 1.0));
+    assert_eq!(*marker, "asteroid");
+    Ok(())
+}
+
+#[test]
+fn overrides_replace_the_values_in_the_prefab() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("speed")?;
+    world.add_prefabs(Prefabs::from_toml(TOML_PREFABS)?);
+
+    world.spawn_prefab(
+        "bullet",
+        &[
+            ("location", Point::new(3.0, 4.0).into()),
+            ("speed", 12.0_f32.into()),
+        ],
+    )?;
+
+    let (location, speed) = world
+        .query_typed::<(&Point, &f32)>(["location", "speed"])?
+        .next()
+        .unwrap();
+    assert_eq!(*location, Point::new(3.0, 4.0));
+    assert_eq!(*speed, 12.0);
+    Ok(())
+}
+
+#[test]
+fn invalid_overrides_name_the_prefab_and_field() -> Result<()> {
+    let mut world = World::new();
+    for name in &["location", "speed", "color"] {
+        world.register(name)?;
+    }
+    world.add_prefabs(Prefabs::from_toml(TOML_PREFABS)?);
+    world.add_prefabs(Prefabs::from_json(JSON_PREFABS)?);
+
+    let error = world
+        .spawn_prefab("bullet", &[("speed", 12_u32.into())])
+        .unwrap_err();
+    assert_invalid(error, "bullet", "speed");
+
+    let error = world
+        .spawn_prefab("bullet", &[("damage", 1_u32.into())])
+        .unwrap_err();
+    assert_invalid(error, "bullet", "damage");
+
+    let error = world
+        .spawn_prefab(
+            "ship",
+            &[("color", PrefabValue::Color([2.0, 0.0, 0.0, 1.0]))],
+        )
+        .unwrap_err();
+    assert_invalid(error, "ship", "color");
+
+    assert_eq!(world.query_typed::<(&Entity,)>([ENTITY_ID])?.count(), 0);
+    Ok(())
+}
+
+#[test]
+fn prefabs_must_match_the_types_already_stored_in_the_world() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("speed")?;
+    world.add_prefabs(Prefabs::from_toml(TOML_PREFABS)?);
+    world.spawn_entity()?.with_component("speed", 5_u32)?;
+
+    let error = world.spawn_prefab("bullet", &[]).unwrap_err();
+
+    assert_invalid(error, "bullet", "speed");
+    assert_eq!(world.query_typed::<(&Entity,)>([ENTITY_ID])?.count(), 1);
+    Ok(())
+}
+
+#[test]
+fn spawning_a_missing_prefab_is_an_error() -> Result<()> {
+    let mut world = World::new();
+    world.add_prefabs(Prefabs::from_toml(TOML_PREFABS)?);
+
+    let error = world.spawn_prefab("comet", &[]).unwrap_err();
+
+    assert!(matches!(
+        error,
+        BbEcsError::PrefabNotFound(name) if name == "comet"
+    ));
+    Ok(())
+}
+
+#[test]
+fn prefab_components_must_be_registered() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.add_prefabs(Prefabs::from_toml(TOML_PREFABS)?);
+
+    let error = world.spawn_prefab("bullet", &[]).unwrap_err();
+
+    assert_invalid(error, "bullet", "speed");
+    assert!(!world.is_registered("speed"));
+    assert_eq!(world.query_typed::<(&Entity,)>([ENTITY_ID])?.count(), 0);
+    Ok(())
+}
+
+#[test]
+fn prefabs_cannot_set_the_transforms_to_other_types() -> Result<()> {
+    let mut world = World::new();
+    let mut prefabs = Prefabs::new();
+    let mut prefab = Prefab::new();
+    prefab.insert(LOCAL_TRANSFORM.to_owned(), Point::new(1.0, 1.0).into());
+    prefabs.insert("moved", prefab);
+    let mut prefab = Prefab::new();
+    prefab.insert(GLOBAL_TRANSFORM.to_owned(), 1.0_f32.into());
+    prefabs.insert("placed", prefab);
+    world.add_prefabs(prefabs);
+
+    let error = world.spawn_prefab("moved", &[]).unwrap_err();
+    assert_invalid(error, "moved", LOCAL_TRANSFORM);
+    let error = world.spawn_prefab("placed", &[]).unwrap_err();
+    assert_invalid(error, "placed", GLOBAL_TRANSFORM);
+
+    assert_eq!(world.query_typed::<(&Entity,)>([ENTITY_ID])?.count(), 0);
+    Ok(())
+}
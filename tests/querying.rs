@@ -1,10 +1,8 @@
-use std::cell::RefCell;
-use std::rc::Rc;
-
 use bbecs::components::CastComponents;
 use bbecs::data_types::point::Point;
-use bbecs::world::{World, WorldMethods};
-use eyre::Result;
+use bbecs::errors::{BbEcsError, Result};
+use bbecs::world::{DataWrapper, World};
+#[cfg(not(feature = "parallel"))]
 use ggez::graphics::Text;
 
 #[test]
@@ -27,8 +25,8 @@ fn querying_for_multiple_components() -> Result<()> {
     let locations = components.get(location_name).unwrap();
     let sizes = components.get(size_name).unwrap();
 
-    let wrapped_location: &Rc<RefCell<Point>> = locations[0].cast()?;
-    let wrapped_size: &Rc<RefCell<u32>> = sizes[0].cast()?;
+    let wrapped_location: &DataWrapper<Point> = locations[0].cast()?;
+    let wrapped_size: &DataWrapper<u32> = sizes[0].cast()?;
 
     let queried_location = wrapped_location.borrow();
     let queried_size = wrapped_size.borrow();
@@ -72,15 +70,17 @@ fn query_for_entities_when_not_all_entities_have_same_number_of_components() ->
 
     assert_eq!(locations.len(), sizes.len());
     assert_eq!(locations.len(), 2);
-    let wrapped_queried_first_location: &Rc<RefCell<Point>> = locations[0].cast()?;
+    let wrapped_queried_first_location: &DataWrapper<Point> = locations[0].cast()?;
     let queried_first_location = wrapped_queried_first_location.borrow();
     assert_eq!(*queried_first_location, first_location);
-    let wrapped_queried_second_location: &Rc<RefCell<Point>> = locations[1].cast()?;
+    let wrapped_queried_second_location: &DataWrapper<Point> = locations[1].cast()?;
     let queried_second_location = wrapped_queried_second_location.borrow();
     assert_eq!(*queried_second_location, third_location);
     Ok(())
 }
 
+// ggez text is not thread safe, so it can only be stored when the world is not shared
+#[cfg(not(feature = "parallel"))]
 #[test]
 fn querying_for_messages() -> Result<()> {
     let mut world = World::new();
@@ -107,3 +107,122 @@ fn querying_for_messages() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn typed_query_for_multiple_components() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("size")?;
+
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(0.0, 0.0))?
+        .with_component("size", 15_u32)?;
+
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(10.0, 10.0))?;
+
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(15.0, 15.0))?
+        .with_component("size", 30_u32)?;
+
+    for (location, mut size) in world.query_typed::<(&Point, &mut u32)>(["location", "size"])? {
+        *size += location.x as u32;
+    }
+
+    let sizes: Vec<u32> = world
+        .query_typed::<(&u32,)>(["size"])?
+        .map(|(size,)| *size)
+        .collect();
+
+    assert_eq!(sizes, vec![15, 45]);
+    Ok(())
+}
+
+#[test]
+fn typed_query_with_the_wrong_type_errors_when_created() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(0.0, 0.0))?;
+
+    let error = world.query_typed::<(&f32,)>(["location"]).err();
+    assert!(matches!(
+        error,
+        Some(BbEcsError::CastingComponents(Some(name), stored, expected))
+            if name == "location"
+                && stored == std::any::type_name::<Point>()
+                && expected == std::any::type_name::<f32>()
+    ));
+    Ok(())
+}
+
+#[test]
+fn typed_query_with_the_wrong_number_of_names_errors() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("size")?;
+
+    let error = world.query_typed::<(&Point, &f32)>(["location"]).err();
+    assert!(matches!(
+        error,
+        Some(BbEcsError::QueryNameCountMismatch(2, 1))
+    ));
+    Ok(())
+}
+
+#[test]
+fn components_stay_with_their_entity_after_moving_between_archetypes() -> Result<()> {
+    let mut world = World::new();
+    world.register("size")?;
+    world.register("frozen")?;
+
+    let entities = (0..5_u32)
+        .map(|size| {
+            world
+                .spawn_entity()
+                .and_then(|mut entity| Ok(entity.with_component("size", size)?.entity()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    world.insert_component(entities[1], "frozen", true)?;
+    world.insert_component(entities[3], "frozen", true)?;
+    world.remove_component::<bool>(entities[1], "frozen")?;
+
+    let mut frozen_sizes: Vec<u32> = world
+        .query_typed::<(&u32, &bool)>(["size", "frozen"])?
+        .map(|(size, _frozen)| *size)
+        .collect();
+    let mut sizes: Vec<u32> = world
+        .query_typed::<(&u32,)>(["size"])?
+        .map(|(size,)| *size)
+        .collect();
+    frozen_sizes.sort_unstable();
+    sizes.sort_unstable();
+
+    assert_eq!(frozen_sizes, vec![3]);
+    assert_eq!(sizes, vec![0, 1, 2, 3, 4]);
+    for (size, entity) in entities.iter().enumerate() {
+        assert_eq!(world.has_component(*entity, "frozen")?, size == 3);
+    }
+    Ok(())
+}
+
+#[test]
+fn typed_query_naming_a_component_twice_errors() -> Result<()> {
+    let mut world = World::new();
+    world.register("size")?;
+    world.spawn_entity()?.with_component("size", 5.0_f32)?;
+
+    let error = world
+        .query_typed::<(&mut f32, &f32)>(["size", "size"])
+        .err();
+    assert!(matches!(
+        error,
+        Some(BbEcsError::DuplicateQueryName(name)) if name == "size"
+    ));
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use bbecs::data_types::point::Point;
+use bbecs::errors::{BbEcsError, Result};
+use bbecs::world::{World, ENTITY_ID};
+
+#[test]
+fn removing_a_component_from_an_entity() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("on fire")?;
+
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(0.0, 0.0))?
+        .with_component("on fire", 1_u32)?;
+    let burning = world
+        .spawn_entity()?
+        .with_component("location", Point::new(10.0, 10.0))?
+        .with_component("on fire", 2_u32)?
+        .entity();
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(20.0, 20.0))?
+        .with_component("on fire", 3_u32)?;
+
+    let removed: u32 = world.remove_component(burning, "on fire")?;
+
+    let still_burning: Vec<(Point, u32)> = world
+        .query_typed::<(&Point, &u32)>(["location", "on fire"])?
+        .map(|(location, on_fire)| (*location, *on_fire))
+        .collect();
+
+    assert_eq!(removed, 2);
+    assert!(!world.has_component(burning, "on fire")?);
+    assert_eq!(
+        still_burning,
+        vec![(Point::new(0.0, 0.0), 1), (Point::new(20.0, 20.0), 3)]
+    );
+    Ok(())
+}
+
+#[test]
+fn removing_a_component_the_entity_does_not_have_is_an_error() -> Result<()> {
+    let mut world = World::new();
+    world.register("on fire")?;
+    let entity = world.spawn_entity()?.entity();
+
+    assert!(world.remove_component::<bool>(entity, "on fire").is_err());
+    Ok(())
+}
+
+#[test]
+fn removing_with_the_wrong_type_keeps_the_component() -> Result<()> {
+    let mut world = World::new();
+    world.register("on fire")?;
+    let entity = world
+        .spawn_entity()?
+        .with_component("on fire", true)?
+        .entity();
+
+    let error = world
+        .remove_component::<u32>(entity, "on fire")
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        BbEcsError::CastingComponents(Some(name), _, _) if name == "on fire"
+    ));
+    assert!(world.has_component(entity, "on fire")?);
+    Ok(())
+}
+
+#[test]
+fn removing_the_entity_id_is_an_error() -> Result<()> {
+    let mut world = World::new();
+    let entity = world.spawn_entity()?.entity();
+
+    assert!(world
+        .remove_component::<bbecs::world::Entity>(entity, ENTITY_ID)
+        .is_err());
+    Ok(())
+}
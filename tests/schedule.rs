@@ -0,0 +1,94 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use bbecs::errors::Result;
+use bbecs::systems::schedule::{Schedule, CLEANUP, INPUT, POST_UPDATE, UPDATE};
+use bbecs::systems::System;
+use bbecs::world::World;
+
+struct RecordRun {
+    name: &'static str,
+    runs: Rc<RefCell<Vec<&'static str>>>,
+}
+
+impl System for RecordRun {
+    fn run(&mut self, _world: &mut World) -> Result<()> {
+        self.runs.borrow_mut().push(self.name);
+        Ok(())
+    }
+}
+
+#[test]
+fn systems_run_in_stage_order() -> Result<()> {
+    let mut world = World::new();
+    let runs = Rc::new(RefCell::new(vec![]));
+    let mut schedule = Schedule::new();
+
+    for (stage, name) in [
+        (CLEANUP, "cleanup"),
+        (UPDATE, "first update"),
+        (INPUT, "input"),
+        (UPDATE, "second update"),
+        (POST_UPDATE, "post update"),
+    ] {
+        schedule.add_system(
+            stage,
+            RecordRun {
+                name,
+                runs: runs.clone(),
+            },
+        )?;
+    }
+    schedule.add_stage_after(UPDATE, "physics")?.add_system(
+        "physics",
+        RecordRun {
+            name: "physics",
+            runs: runs.clone(),
+        },
+    )?;
+
+    schedule.run(&mut world)?;
+
+    assert_eq!(
+        *runs.borrow(),
+        vec![
+            "input",
+            "first update",
+            "second update",
+            "physics",
+            "post update",
+            "cleanup"
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn the_schedule_removes_deleted_entities_at_the_configured_stage() -> Result<()> {
+    let mut world = World::new();
+    let entity = world.spawn_entity()?.entity();
+
+    let mut schedule = Schedule::new();
+    schedule
+        .add_system(UPDATE, move |world: &mut World| world.delete_by_id(entity))?
+        .add_system(POST_UPDATE, move |world: &mut World| {
+            assert!(!world.is_alive(entity));
+            Ok(())
+        })?
+        .run_world_update_after(UPDATE)?;
+
+    schedule.run(&mut world)?;
+
+    assert!(schedule.run_world_update_after("missing").is_err());
+    Ok(())
+}
+
+#[test]
+fn adding_a_system_to_a_missing_stage_is_an_error() {
+    let mut schedule = Schedule::empty();
+    assert!(schedule
+        .add_system("missing", |_world: &mut World| Ok(()))
+        .is_err());
+    assert!(schedule.add_stage("new").is_ok());
+    assert!(schedule.add_stage("new").is_err());
+}
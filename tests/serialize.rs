@@ -0,0 +1,307 @@
+#![cfg(feature = "serialize")]
+
+use bbecs::data_types::point::Point;
+use bbecs::errors::{BbEcsError, Result};
+use bbecs::resources::resource::ResourceCast;
+use bbecs::world::serialize::{SavedValue, Value, WorldSerializer};
+use bbecs::world::{Entity, World, WorldMethods, ENTITY_ID};
+use ggez::graphics::Color;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Weapon {
+    name: String,
+    damage: u32,
+}
+
+struct NotSaved;
+
+fn check_loaded(world: &World, player: Entity, enemy: Entity) -> Result<()> {
+    let (location, color, weapon) = world
+        .query_typed::<(&Point, &Color, &Weapon)>(["location", "color", "weapon"])?
+        .next()
+        .unwrap();
+    assert_eq!(*location, Point::new(1.0, 2.0));
+    assert_eq!(*color, Color::new(1.0, 0.0, 0.0, 1.0));
+    assert_eq!(weapon.damage, 3);
+    drop((location, color, weapon));
+
+    let (id, target) = world
+        .query_typed::<(&Entity, &Entity)>([ENTITY_ID, "target"])?
+        .next()
+        .unwrap();
+    assert_eq!(*id, enemy);
+    assert_eq!(*target, player);
+    assert_eq!(world.parent(enemy), Some(player));
+    assert!(!world.has_component(player, "mesh")?);
+
+    let wrapper = world.get_resource("score")?.borrow();
+    let score: &u32 = wrapper.cast()?;
+    assert_eq!(*score, 42);
+    assert!(world.get_resource("unsaved").is_err());
+    Ok(())
+}
+
+#[test]
+fn worlds_round_trip_through_json() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("color")?;
+    world.register("weapon")?;
+    world.register("target")?;
+    world.register("mesh")?;
+    let player = world
+        .spawn_entity()?
+        .with_component("location", Point::new(1.0, 2.0))?
+        .with_component("color", Color::new(1.0, 0.0, 0.0, 1.0))?
+        .with_component(
+            "weapon",
+            Weapon {
+                name: "laser".to_owned(),
+                damage: 3,
+            },
+        )?
+        .with_component("mesh", NotSaved)?
+        .entity();
+    let enemy = world
+        .spawn_entity()?
+        .with_component("location", Point::new(10.0, 2.0))?
+        .with_component("target", player)?
+        .with_parent(player)?
+        .entity();
+    world.add_resource("score".to_owned(), 42_u32);
+    world.add_resource("unsaved".to_owned(), NotSaved);
+    let mut serializer = WorldSerializer::new();
+    serializer.register::<Weapon>("Weapon");
+    let json = serializer.to_json(&world)?;
+
+    let mut loaded = World::new();
+    let entities = serializer.from_json(&mut loaded, &json)?;
+
+    assert_eq!(entities.len(), 2);
+    check_loaded(&loaded, entities.get(player)?, entities.get(enemy)?)
+}
+
+#[test]
+fn worlds_round_trip_through_ron() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("color")?;
+    world.register("weapon")?;
+    world.register("target")?;
+    world.register("mesh")?;
+    let player = world
+        .spawn_entity()?
+        .with_component("location", Point::new(1.0, 2.0))?
+        .with_component("color", Color::new(1.0, 0.0, 0.0, 1.0))?
+        .with_component(
+            "weapon",
+            Weapon {
+                name: "laser".to_owned(),
+                damage: 3,
+            },
+        )?
+        .with_component("mesh", NotSaved)?
+        .entity();
+    let enemy = world
+        .spawn_entity()?
+        .with_component("location", Point::new(10.0, 2.0))?
+        .with_component("target", player)?
+        .with_parent(player)?
+        .entity();
+    world.add_resource("score".to_owned(), 42_u32);
+    world.add_resource("unsaved".to_owned(), NotSaved);
+    let mut serializer = WorldSerializer::new();
+    serializer.register::<Weapon>("Weapon");
+    let ron = serializer.to_ron(&world)?;
+
+    let mut loaded = World::new();
+    let entities = serializer.from_ron(&mut loaded, &ron)?;
+
+    check_loaded(&loaded, entities.get(player)?, entities.get(enemy)?)
+}
+
+#[test]
+fn entities_are_remapped_when_loading_into_a_world_with_entities() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("color")?;
+    world.register("weapon")?;
+    world.register("target")?;
+    world.register("mesh")?;
+    let player = world
+        .spawn_entity()?
+        .with_component("location", Point::new(1.0, 2.0))?
+        .with_component("color", Color::new(1.0, 0.0, 0.0, 1.0))?
+        .with_component(
+            "weapon",
+            Weapon {
+                name: "laser".to_owned(),
+                damage: 3,
+            },
+        )?
+        .with_component("mesh", NotSaved)?
+        .entity();
+    let enemy = world
+        .spawn_entity()?
+        .with_component("location", Point::new(10.0, 2.0))?
+        .with_component("target", player)?
+        .with_parent(player)?
+        .entity();
+    world.add_resource("score".to_owned(), 42_u32);
+    world.add_resource("unsaved".to_owned(), NotSaved);
+    let mut serializer = WorldSerializer::new();
+    serializer.register::<Weapon>("Weapon");
+    let json = serializer.to_json(&world)?;
+
+    let mut loaded = World::new();
+    for _ in 0..3 {
+        loaded.spawn_entity()?;
+    }
+    let entities = serializer.from_json(&mut loaded, &json)?;
+
+    assert_ne!(entities.get(player)?, player);
+    let (target,) = loaded
+        .query_typed::<(&Entity,)>(["target"])?
+        .next()
+        .unwrap();
+    assert_eq!(*target, entities.get(player)?);
+    assert_eq!(loaded.parent(entities.get(enemy)?), Some(*target));
+    Ok(())
+}
+
+#[test]
+fn entities_flagged_to_be_deleted_are_not_saved() -> Result<()> {
+    let mut world = World::new();
+    world.register("target")?;
+    let player = world.spawn_entity()?.entity();
+    let enemy = world
+        .spawn_entity()?
+        .with_component("target", player)?
+        .entity();
+    world.delete_by_id(enemy)?;
+
+    let saved = WorldSerializer::new().save(&world)?;
+
+    assert_eq!(saved.entities.len(), 1);
+    assert_eq!(saved.entities[0].id, player);
+    Ok(())
+}
+
+#[test]
+fn loading_a_type_that_is_not_registered_is_an_error() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("weapon")?;
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(1.0, 2.0))?;
+    world.spawn_entity()?.with_component(
+        "weapon",
+        Weapon {
+            name: "laser".to_owned(),
+            damage: 3,
+        },
+    )?;
+    let mut serializer = WorldSerializer::new();
+    serializer.register::<Weapon>("Weapon");
+    let json = serializer.to_json(&world)?;
+
+    let mut loaded = World::new();
+    let error = WorldSerializer::new()
+        .from_json(&mut loaded, &json)
+        .unwrap_err();
+
+    assert!(matches!(
+        error,
+        BbEcsError::UnknownSavedType(key) if key == "Weapon"
+    ));
+    assert_eq!(loaded.query_typed::<(&Entity,)>([ENTITY_ID])?.len(), 0);
+    assert!(!loaded.is_registered("location"));
+    Ok(())
+}
+
+#[test]
+fn entities_whose_parent_is_not_saved_are_saved_without_a_parent() -> Result<()> {
+    let mut world = World::new();
+    let parent = world.spawn_entity()?.entity();
+    let child = world.spawn_entity()?.with_parent(parent)?.entity();
+    world.delete_by_id(parent)?;
+
+    let serializer = WorldSerializer::new();
+    let json = serializer.to_json(&world)?;
+    let mut loaded = World::new();
+    let entities = serializer.from_json(&mut loaded, &json)?;
+
+    assert_eq!(entities.len(), 1);
+    assert_eq!(loaded.parent(entities.get(child)?), None);
+    Ok(())
+}
+
+#[test]
+fn entities_that_no_longer_exist_are_loaded_as_dangling() -> Result<()> {
+    let mut world = World::new();
+    world.register("target")?;
+    let target = world.spawn_entity()?.entity();
+    world.spawn_entity()?.with_component("target", target)?;
+    world.despawn(target)?;
+
+    let serializer = WorldSerializer::new();
+    let json = serializer.to_json(&world)?;
+    let mut loaded = World::new();
+    serializer.from_json(&mut loaded, &json)?;
+
+    let (target,) = loaded
+        .query_typed::<(&Entity,)>(["target"])?
+        .next()
+        .unwrap();
+    assert_eq!(*target, Entity::DANGLING);
+    assert!(!loaded.is_alive(*target));
+    Ok(())
+}
+
+#[test]
+fn saves_that_cannot_be_loaded_leave_the_world_unchanged() -> Result<()> {
+    let mut world = World::new();
+    world.register("size")?;
+    let parent = world
+        .spawn_entity()?
+        .with_component("size", 1_u32)?
+        .entity();
+    world.spawn_entity()?.with_parent(parent)?;
+    let serializer = WorldSerializer::new();
+    let mut saved = serializer.save(&world)?;
+    saved.entities.retain(|entity| entity.id != parent);
+
+    let mut loaded = World::new();
+    let error = serializer.load(&mut loaded, saved).unwrap_err();
+    assert!(matches!(error, BbEcsError::EntityNotSaved(entity) if entity == parent));
+
+    let mut saved = serializer.save(&world)?;
+    for entity in &mut saved.entities {
+        if let Some(size) = entity.components.get_mut("size") {
+            size.value = Value::from(-1);
+        }
+    }
+    assert!(serializer.load(&mut loaded, saved).is_err());
+
+    let mut saved = serializer.save(&world)?;
+    for entity in &mut saved.entities {
+        entity
+            .components
+            .entry("size".to_owned())
+            .or_insert(SavedValue {
+                type_key: "f32".to_owned(),
+                value: Value::from(2.5),
+            });
+    }
+    let error = serializer.load(&mut loaded, saved).unwrap_err();
+    assert!(matches!(
+        error,
+        BbEcsError::ComponentTypeMismatch(name, _, _) if name == "size"
+    ));
+
+    assert_eq!(loaded.query_typed::<(&Entity,)>([ENTITY_ID])?.len(), 0);
+    assert!(!loaded.is_registered("size"));
+    Ok(())
+}
@@ -0,0 +1,227 @@
+use bbecs::data_types::point::Point;
+use bbecs::errors::{BbEcsError, Result};
+use bbecs::resources::resource::ResourceCast;
+use bbecs::world::snapshot::{Encode, Reader, Snapshot, Snapshotter, SNAPSHOT_VERSION};
+use bbecs::world::{Entity, World, WorldMethods, ENTITY_ID};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Health(u32);
+
+impl Encode for Health {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        self.0.encode(buffer);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+        Ok(Health(u32::decode(reader)?))
+    }
+}
+
+struct Mesh;
+
+fn location(world: &World, entity: Entity) -> Result<Point> {
+    let location = world
+        .query_typed::<(&Entity, &Point)>([ENTITY_ID, "location"])?
+        .find(|(other, _)| **other == entity)
+        .map(|(_, location)| *location)
+        .unwrap();
+    Ok(location)
+}
+
+#[test]
+fn restoring_rewinds_components_resources_and_despawned_entities() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("health")?;
+    world.register("mesh")?;
+    let player = world
+        .spawn_entity()?
+        .with_component("location", Point::new(1.0, 2.0))?
+        .with_component("health", Health(10))?
+        .with_component("mesh", Mesh)?
+        .entity();
+    let shield = world
+        .spawn_entity()?
+        .with_component("health", Health(5))?
+        .with_parent(player)?
+        .entity();
+    world.add_resource("score".to_owned(), 42_u32);
+    let mut snapshotter = Snapshotter::new();
+    snapshotter.register::<Health>("Health");
+    let snapshot = snapshotter.take(&world)?;
+
+    world.insert_component(player, "location", Point::new(50.0, 50.0))?;
+    world.despawn(shield)?;
+    world.add_resource("score".to_owned(), 0_u32);
+
+    snapshotter.restore(&mut world, &snapshot)?;
+
+    assert_eq!(location(&world, player)?, Point::new(1.0, 2.0));
+    assert!(world.is_alive(shield));
+    assert_eq!(world.parent(shield), Some(player));
+    let health: Vec<Health> = world
+        .query_typed::<(&Health,)>(["health"])?
+        .map(|(health,)| *health)
+        .collect();
+    assert_eq!(health.len(), 2);
+    let wrapper = world.get_resource("score")?.borrow();
+    let score: &u32 = wrapper.cast()?;
+    assert_eq!(*score, 42);
+    Ok(())
+}
+
+#[test]
+fn entities_spawned_after_the_snapshot_are_removed() -> Result<()> {
+    let mut world = World::new();
+    world.register("health")?;
+    let shield = world
+        .spawn_entity()?
+        .with_component("health", Health(5))?
+        .entity();
+    let mut snapshotter = Snapshotter::new();
+    snapshotter.register::<Health>("Health");
+    let snapshot = snapshotter.take(&world)?;
+
+    world.despawn(shield)?;
+    let bullet = world.spawn_entity()?.entity();
+    assert_eq!(bullet.index(), shield.index());
+
+    snapshotter.restore(&mut world, &snapshot)?;
+
+    assert!(!world.is_alive(bullet));
+    assert!(world.is_alive(shield));
+    let next = world.spawn_entity()?.entity();
+    assert_ne!(next.index(), shield.index());
+    Ok(())
+}
+
+#[test]
+fn components_without_a_codec_are_kept() -> Result<()> {
+    let mut world = World::new();
+    world.register("health")?;
+    world.register("mesh")?;
+    let player = world
+        .spawn_entity()?
+        .with_component("health", Health(10))?
+        .with_component("mesh", Mesh)?
+        .entity();
+    let mut snapshotter = Snapshotter::new();
+    snapshotter.register::<Health>("Health");
+    let snapshot = snapshotter.take(&world)?;
+
+    snapshotter.restore(&mut world, &snapshot)?;
+
+    assert!(world.has_component(player, "mesh")?);
+    assert!(world.has_component(player, "health")?);
+    Ok(())
+}
+
+#[test]
+fn snapshots_round_trip_through_bytes() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("health")?;
+    let player = world
+        .spawn_entity()?
+        .with_component("location", Point::new(1.0, 2.0))?
+        .with_component("health", Health(10))?
+        .entity();
+    world
+        .spawn_entity()?
+        .with_component("health", Health(5))?
+        .with_parent(player)?;
+    world.add_resource("score".to_owned(), 42_u32);
+    let mut snapshotter = Snapshotter::new();
+    snapshotter.register::<Health>("Health");
+    let snapshot = snapshotter.take(&world)?;
+
+    let bytes = snapshot.to_bytes();
+    let loaded = Snapshot::from_bytes(&bytes)?;
+    assert_eq!(loaded, snapshot);
+
+    world.insert_component(player, "location", Point::new(9.0, 9.0))?;
+    snapshotter.restore(&mut world, &loaded)?;
+    assert_eq!(location(&world, player)?, Point::new(1.0, 2.0));
+    Ok(())
+}
+
+#[test]
+fn corrupted_snapshots_and_other_versions_are_rejected() -> Result<()> {
+    let mut world = World::new();
+    world.register("health")?;
+    world.spawn_entity()?.with_component("health", Health(10))?;
+    let mut snapshotter = Snapshotter::new();
+    snapshotter.register::<Health>("Health");
+    let bytes = snapshotter.take(&world)?.to_bytes();
+    let error = Snapshot::from_bytes(&bytes[..bytes.len() / 2]).unwrap_err();
+    assert!(matches!(error, BbEcsError::SnapshotCorrupted(_)));
+
+    let mut bytes = bytes;
+    bytes[4..8].copy_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+
+    let error = Snapshot::from_bytes(&bytes).unwrap_err();
+    assert!(matches!(
+        error,
+        BbEcsError::SnapshotVersion(found, expected)
+            if found == SNAPSHOT_VERSION + 1 && expected == SNAPSHOT_VERSION
+    ));
+    Ok(())
+}
+
+#[test]
+fn diffs_list_what_changed_between_snapshots() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("health")?;
+    world.register("mesh")?;
+    let player = world
+        .spawn_entity()?
+        .with_component("location", Point::new(1.0, 2.0))?
+        .with_component("health", Health(10))?
+        .with_component("mesh", Mesh)?
+        .entity();
+    let shield = world
+        .spawn_entity()?
+        .with_component("health", Health(5))?
+        .with_parent(player)?
+        .entity();
+    world.add_resource("score".to_owned(), 42_u32);
+    let mut snapshotter = Snapshotter::new();
+    snapshotter.register::<Health>("Health");
+    let before = snapshotter.take(&world)?;
+    assert!(before.diff(&snapshotter.take(&world)?).is_empty());
+
+    world.insert_component(player, "health", Health(3))?;
+    world.despawn(shield)?;
+    let bullet = world.spawn_entity()?.entity();
+    world.add_resource("score".to_owned(), 43_u32);
+    let after = snapshotter.take(&world)?;
+
+    let diff = before.diff(&after);
+    assert_eq!(diff.changed, vec![(player, "health".to_owned())]);
+    assert_eq!(diff.despawned, vec![shield]);
+    assert_eq!(diff.spawned, vec![bullet]);
+    assert_eq!(diff.resources, vec!["score".to_owned()]);
+    Ok(())
+}
+
+#[test]
+fn component_names_are_written_once_for_each_archetype() -> Result<()> {
+    let mut world = World::new();
+    world.register("health")?;
+    for health in 0..100 {
+        world
+            .spawn_entity()?
+            .with_component("health", Health(health))?;
+    }
+    let mut snapshotter = Snapshotter::new();
+    snapshotter.register::<Health>("Health");
+
+    let bytes = snapshotter.take(&world)?.to_bytes();
+    let names = bytes
+        .windows("health".len())
+        .filter(|window| *window == b"health")
+        .count();
+    assert_eq!(names, 1);
+    Ok(())
+}
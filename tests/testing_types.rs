@@ -3,12 +3,10 @@ macro_rules! test_world_by_type {
         #[allow(clippy::float_cmp)]
         mod $name {
             use bbecs::components::CastComponents;
+            use bbecs::errors::Result;
             use bbecs::query;
             use bbecs::resources::resource::ResourceCast;
-            use bbecs::world::{World, WorldMethods};
-            use eyre::Result;
-            use std::cell::RefCell;
-            use std::rc::Rc;
+            use bbecs::world::{DataWrapper, World, WorldMethods};
             #[test]
             fn cast_resource_immutably() -> Result<()> {
                 let mut world = World::new();
@@ -44,7 +42,7 @@ macro_rules! test_world_by_type {
 
                 let query;
                 let components = query!(world, query, "component");
-                let wrapped_component: &Rc<RefCell<$type>> = components.0[0].cast()?;
+                let wrapped_component: &DataWrapper<$type> = components.0[0].cast()?;
                 assert_eq!(*wrapped_component.borrow(), <$type>::default());
                 Ok(())
             }
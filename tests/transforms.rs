@@ -0,0 +1,180 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bbecs::data_types::point::Point;
+use bbecs::data_types::transform::Transform;
+use bbecs::errors::{BbEcsError, Result};
+use bbecs::world::query::QueryFilter;
+use bbecs::world::{Entity, World, ENTITY_ID, GLOBAL_TRANSFORM, LOCAL_TRANSFORM};
+
+fn global_transform(world: &World, entity: Entity) -> Result<Transform> {
+    let transform = world
+        .query_typed::<(&Entity, &Transform)>([ENTITY_ID, GLOBAL_TRANSFORM])?
+        .find(|(other, _)| **other == entity)
+        .map(|(_, transform)| *transform)
+        .unwrap();
+    Ok(transform)
+}
+
+fn assert_close(actual: Point, expected: Point) {
+    assert!(
+        actual.distance_to(&expected) < 0.0001,
+        "expected {:?} to be close to {:?}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn children_are_moved_rotated_and_scaled_by_their_parents() -> Result<()> {
+    let mut world = World::new();
+    let ship = world
+        .spawn_entity()?
+        .with_component(
+            LOCAL_TRANSFORM,
+            Transform::new(Point::new(10.0, 10.0))
+                .with_rotation(FRAC_PI_2)
+                .with_scale(Point::new(2.0, 2.0)),
+        )?
+        .entity();
+    let turret = world
+        .spawn_entity()?
+        .with_component(LOCAL_TRANSFORM, Transform::new(Point::new(1.0, 0.0)))?
+        .with_parent(ship)?
+        .entity();
+    let barrel = world
+        .spawn_entity()?
+        .with_component(LOCAL_TRANSFORM, Transform::new(Point::new(0.0, 1.0)))?
+        .with_parent(turret)?
+        .entity();
+
+    world.update()?;
+
+    assert_close(
+        global_transform(&world, ship)?.position,
+        Point::new(10.0, 10.0),
+    );
+    assert_close(
+        global_transform(&world, turret)?.position,
+        Point::new(10.0, 12.0),
+    );
+    let barrel_transform = global_transform(&world, barrel)?;
+    assert_close(barrel_transform.position, Point::new(8.0, 12.0));
+    assert_eq!(barrel_transform.rotation, FRAC_PI_2);
+    assert_eq!(barrel_transform.scale, Point::new(2.0, 2.0));
+    Ok(())
+}
+
+#[test]
+fn moving_a_parent_moves_its_children_on_the_next_update() -> Result<()> {
+    let mut world = World::new();
+    let ship = world
+        .spawn_entity()?
+        .with_component(LOCAL_TRANSFORM, Transform::new(Point::new(0.0, 0.0)))?
+        .entity();
+    let turret = world
+        .spawn_entity()?
+        .with_component(LOCAL_TRANSFORM, Transform::new(Point::new(1.0, 0.0)))?
+        .with_parent(ship)?
+        .entity();
+    world.update()?;
+
+    world.insert_component(ship, LOCAL_TRANSFORM, Transform::new(Point::new(5.0, 0.0)))?;
+    world.update()?;
+
+    assert_close(
+        global_transform(&world, turret)?.position,
+        Point::new(6.0, 0.0),
+    );
+    Ok(())
+}
+
+#[test]
+fn entities_without_a_local_transform_pass_on_their_parents_transform() -> Result<()> {
+    let mut world = World::new();
+    let ship = world
+        .spawn_entity()?
+        .with_component(LOCAL_TRANSFORM, Transform::new(Point::new(3.0, 4.0)))?
+        .entity();
+    let group = world.spawn_entity()?.with_parent(ship)?.entity();
+    let light = world
+        .spawn_entity()?
+        .with_component(LOCAL_TRANSFORM, Transform::new(Point::new(1.0, 1.0)))?
+        .with_parent(group)?
+        .entity();
+
+    world.propagate_transforms()?;
+
+    assert!(!world.has_component(group, GLOBAL_TRANSFORM)?);
+    assert_close(
+        global_transform(&world, light)?.position,
+        Point::new(4.0, 5.0),
+    );
+    Ok(())
+}
+
+#[test]
+fn global_transforms_are_only_changed_when_they_move() -> Result<()> {
+    let mut world = World::new();
+    let ship = world
+        .spawn_entity()?
+        .with_component(LOCAL_TRANSFORM, Transform::new(Point::new(0.0, 0.0)))?
+        .entity();
+    world
+        .spawn_entity()?
+        .with_component(LOCAL_TRANSFORM, Transform::new(Point::new(2.0, 0.0)))?;
+    world.propagate_transforms()?;
+
+    let last_run = world.change_tick();
+    world.update()?;
+    world.insert_component(ship, LOCAL_TRANSFORM, Transform::new(Point::new(1.0, 0.0)))?;
+    world.update()?;
+
+    let filter = QueryFilter::new().changed(GLOBAL_TRANSFORM).since(last_run);
+    let changed: Vec<Entity> = world
+        .query_typed_filtered::<(&Entity,)>([ENTITY_ID], &filter)?
+        .map(|(entity,)| *entity)
+        .collect();
+    assert_eq!(changed, vec![ship]);
+    Ok(())
+}
+
+#[test]
+fn transform_components_only_store_transforms() -> Result<()> {
+    let mut world = World::new();
+    world.register(GLOBAL_TRANSFORM)?;
+    world.register(LOCAL_TRANSFORM)?;
+    let entity = world.spawn_entity()?.entity();
+
+    let error = world
+        .insert_component(entity, LOCAL_TRANSFORM, 1.0_f32)
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        BbEcsError::ComponentTypeMismatch(name, _, _) if name == LOCAL_TRANSFORM
+    ));
+    assert!(world
+        .spawn_bundle(((GLOBAL_TRANSFORM, Point::new(0.0, 0.0)),))
+        .is_err());
+
+    world.insert_component(entity, LOCAL_TRANSFORM, Transform::default())?;
+    world.update()?;
+    assert_eq!(global_transform(&world, entity)?, Transform::default());
+    Ok(())
+}
+
+#[test]
+fn removing_the_local_transform_removes_the_global_transform() -> Result<()> {
+    let mut world = World::new();
+    let ship = world
+        .spawn_entity()?
+        .with_component(LOCAL_TRANSFORM, Transform::new(Point::new(1.0, 0.0)))?
+        .entity();
+    world.update()?;
+    assert!(world.has_component(ship, GLOBAL_TRANSFORM)?);
+
+    world.remove_component::<Transform>(ship, LOCAL_TRANSFORM)?;
+    world.update()?;
+
+    assert!(!world.has_component(ship, GLOBAL_TRANSFORM)?);
+    Ok(())
+}
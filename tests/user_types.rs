@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use bbecs::components::CastComponents;
+use bbecs::data_types::point::Point;
+use bbecs::errors::{BbEcsError, Result};
+use bbecs::resources::resource::ResourceCast;
+use bbecs::world::{DataWrapper, World, WorldMethods};
+use bbecs::{get_resource, get_resource_mut};
+
+#[derive(Debug, PartialEq)]
+struct Velocity {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Debug, PartialEq)]
+enum AiState {
+    Idle,
+    Chasing(u32),
+}
+
+#[test]
+fn querying_for_user_defined_components() -> Result<()> {
+    let mut world = World::new();
+    world.register("location")?;
+    world.register("velocity")?;
+    world.register("ai state")?;
+
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(0.0, 0.0))?
+        .with_component("velocity", Velocity { x: 1.0, y: 2.0 })?
+        .with_component("ai state", AiState::Idle)?;
+
+    world
+        .spawn_entity()?
+        .with_component("location", Point::new(5.0, 5.0))?
+        .with_component("ai state", AiState::Chasing(0))?;
+
+    let query_results = world.query(vec!["velocity", "ai state"])?;
+    let velocities = query_results.get("velocity").unwrap();
+    let ai_states = query_results.get("ai state").unwrap();
+
+    assert_eq!(velocities.len(), 1);
+    let velocity: &DataWrapper<Velocity> = velocities[0].cast()?;
+    let ai_state: &DataWrapper<AiState> = ai_states[0].cast()?;
+    assert_eq!(*velocity.borrow(), Velocity { x: 1.0, y: 2.0 });
+    assert_eq!(*ai_state.borrow(), AiState::Idle);
+    Ok(())
+}
+
+#[test]
+fn casting_to_the_wrong_type_is_an_error() -> Result<()> {
+    let mut world = World::new();
+    world.register("velocity")?;
+    world
+        .spawn_entity()?
+        .with_component("velocity", Velocity { x: 1.0, y: 2.0 })?;
+
+    let query_results = world.query(vec!["velocity"])?;
+    let velocities = query_results.get("velocity").unwrap();
+    let cast_result: Result<&DataWrapper<Point>> = velocities[0].cast();
+
+    assert!(matches!(
+        cast_result,
+        Err(BbEcsError::CastingComponents(Some(name), _, _)) if name == "velocity"
+    ));
+    Ok(())
+}
+
+#[test]
+fn inserting_a_different_type_under_the_same_name_is_an_error() -> Result<()> {
+    let mut world = World::new();
+    world.register("velocity")?;
+    world
+        .spawn_entity()?
+        .with_component("velocity", Velocity { x: 1.0, y: 2.0 })?;
+
+    let mut entity = world.spawn_entity()?;
+    let result = entity.with_component("velocity", Point::new(1.0, 2.0));
+
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[test]
+fn adding_and_mutating_a_user_defined_resource() -> Result<()> {
+    let mut world = World::new();
+    let mut scores = HashMap::new();
+    scores.insert("player one".to_owned(), 10_u32);
+    world.add_resource("scores".to_owned(), scores);
+
+    {
+        let scores: &mut HashMap<String, u32>;
+        get_resource_mut!(scores, world, "scores");
+        *scores.get_mut("player one").unwrap() += 5;
+    }
+
+    let scores: &HashMap<String, u32>;
+    get_resource!(scores, world, "scores");
+    assert_eq!(scores["player one"], 15);
+    Ok(())
+}
+
+#[test]
+fn casting_a_resource_to_the_wrong_type_is_an_error() -> Result<()> {
+    let mut world = World::new();
+    world.add_resource("velocity".to_owned(), Velocity { x: 1.0, y: 2.0 });
+
+    let wrapped_resource = world.get_resource("velocity")?.borrow();
+    let cast_result: Result<&Point> = wrapped_resource.cast();
+    let error = cast_result.unwrap_err();
+
+    assert!(matches!(
+        error,
+        BbEcsError::CastingResource(None, stored, expected)
+            if stored.ends_with("Velocity") && expected == std::any::type_name::<Point>()
+    ));
+    Ok(())
+}